@@ -0,0 +1,15 @@
+//! Archives edited along with the learnsets.
+
+use crate::{
+    egg_moves::EggMoveFile, evolution::EvolutionFile, move_data::MoveDataFile,
+    personal::PersonalFile,
+};
+
+/// Archives edited besides the learnsets, available when opening a ROM or given on their own.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct Archives {
+    pub personal: Option<PersonalFile>,
+    pub egg_moves: Option<EggMoveFile>,
+    pub evolutions: Option<EvolutionFile>,
+    pub move_data: Option<MoveDataFile>,
+}
@@ -0,0 +1,41 @@
+//! Bounds-checked little-endian readers shared by the container formats.
+
+use std::{fmt::Display, ops::Range};
+
+/// Attempt to read past the end of the data.
+#[derive(Debug)]
+pub struct UnexpectedEnd {
+    /// Where the data ends.
+    pub offset: usize,
+    pub needed: usize,
+}
+
+impl Display for UnexpectedEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "data ends at offset {:#x}, {} more bytes were expected",
+            self.offset, self.needed
+        )
+    }
+}
+
+pub fn slice(data: &[u8], range: Range<usize>) -> Result<&[u8], UnexpectedEnd> {
+    data.get(range.clone()).ok_or_else(|| UnexpectedEnd {
+        offset: data.len(),
+        needed: range.end.max(range.start) - data.len(),
+    })
+}
+
+pub fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], UnexpectedEnd> {
+    let bytes = slice(data, offset..offset + N)?;
+    Ok(bytes.try_into().expect("the slice is N bytes long"))
+}
+
+pub fn read_u16(data: &[u8], offset: usize) -> Result<u16, UnexpectedEnd> {
+    read_bytes(data, offset).map(u16::from_le_bytes)
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Result<u32, UnexpectedEnd> {
+    read_bytes(data, offset).map(u32::from_le_bytes)
+}
@@ -0,0 +1,256 @@
+//! Validation of learnsets, for data the games would misbehave with.
+
+use std::fmt::Display;
+
+use crate::{
+    file::{MAX_LEVEL, MIN_LEVEL, Move, SaveFile, pokemon_name},
+    names::MOVE_NAMES,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    LevelTooLow(u16),
+    LevelTooHigh(u16),
+    UnknownMove(u16),
+    /// Same move at the same level as the entry at the given index.
+    Duplicate(usize),
+    /// Learned at a lower level than the entry before it.
+    OutOfOrder {
+        previous_level: u16,
+    },
+}
+
+/// Something wrong with one entry of a learnset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Problem {
+    pub pokemon: usize,
+    /// Index of the move in the learnset.
+    pub index: usize,
+    pub kind: Kind,
+}
+
+impl Problem {
+    #[must_use]
+    pub const fn severity(&self) -> Severity {
+        match self.kind {
+            Kind::LevelTooLow(_) | Kind::LevelTooHigh(_) | Kind::UnknownMove(_) => Severity::Error,
+            Kind::Duplicate(_) | Kind::OutOfOrder { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LevelTooLow(level) => write!(f, "level {level} is below {MIN_LEVEL}"),
+            Self::LevelTooHigh(level) => write!(f, "level {level} is above {MAX_LEVEL}"),
+            Self::UnknownMove(id) => {
+                write!(f, "move id {id} is not between 1 and {}", MOVE_NAMES.len())
+            }
+            Self::Duplicate(index) => write!(f, "same move and level as move {}", index + 1),
+            Self::OutOfOrder { previous_level } => {
+                write!(f, "learned before the move at level {previous_level}")
+            }
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, move {}: {}",
+            pokemon_name(self.pokemon),
+            self.index + 1,
+            self.kind
+        )
+    }
+}
+
+/// Lists the problems of every learnset, in file order.
+#[must_use]
+pub fn check(save_file: &SaveFile) -> Vec<Problem> {
+    save_file
+        .pokemons
+        .iter()
+        .enumerate()
+        .flat_map(|(pokemon, moves)| {
+            learnset(moves).map(move |(index, kind)| Problem {
+                pokemon,
+                index,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Number of errors and warnings among the problems found by [`check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl Summary {
+    #[must_use]
+    pub fn of(problems: &[Problem]) -> Self {
+        let errors = problems
+            .iter()
+            .filter(|problem| problem.severity() == Severity::Error)
+            .count();
+        Self {
+            errors,
+            warnings: problems.len() - errors,
+        }
+    }
+
+    /// Whether the check fails: on any error, and on any warning too when `strict`.
+    #[must_use]
+    pub const fn fails(self, strict: bool) -> bool {
+        self.errors > 0 || (strict && self.warnings > 0)
+    }
+}
+
+fn learnset(moves: &[Move]) -> impl Iterator<Item = (usize, Kind)> {
+    moves.iter().enumerate().flat_map(move |(index, &pmove)| {
+        let level = match pmove.level {
+            level if level < MIN_LEVEL => Some(Kind::LevelTooLow(level)),
+            level if level > MAX_LEVEL => Some(Kind::LevelTooHigh(level)),
+            _ => None,
+        };
+        let unknown_move = (!(1..=MOVE_NAMES.len()).contains(&usize::from(pmove.id)))
+            .then_some(Kind::UnknownMove(pmove.id));
+        let duplicate = moves[..index]
+            .iter()
+            .position(|&other| other == pmove)
+            .map(Kind::Duplicate);
+        let out_of_order = index
+            .checked_sub(1)
+            .map(|previous| moves[previous].level)
+            .filter(|&previous_level| previous_level > pmove.level)
+            .map(|previous_level| Kind::OutOfOrder { previous_level });
+        [level, unknown_move, duplicate, out_of_order]
+            .into_iter()
+            .flatten()
+            .map(move |kind| (index, kind))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TACKLE: u16 = 33;
+    const GROWL: u16 = 45;
+
+    /// Problems of a single learnset, as `(index, kind, severity)`.
+    fn problems(moves: Vec<Move>) -> Vec<(usize, Kind, Severity)> {
+        check(&SaveFile::from_learnsets(vec![moves]))
+            .into_iter()
+            .map(|problem| (problem.index, problem.kind, problem.severity()))
+            .collect()
+    }
+
+    #[test]
+    fn valid_learnsets_have_no_problems() {
+        let moves = vec![Move::new(TACKLE, MIN_LEVEL), Move::new(GROWL, MAX_LEVEL)];
+        assert!(problems(moves).is_empty());
+    }
+
+    #[test]
+    fn levels_out_of_range_are_errors() {
+        assert_eq!(
+            problems(vec![Move::new(TACKLE, 0)]),
+            [(0, Kind::LevelTooLow(0), Severity::Error)]
+        );
+        assert_eq!(
+            problems(vec![Move::new(TACKLE, MAX_LEVEL + 1)]),
+            [(0, Kind::LevelTooHigh(MAX_LEVEL + 1), Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn unknown_moves_are_errors() {
+        let past_the_end = u16::try_from(MOVE_NAMES.len() + 1).unwrap();
+        assert_eq!(
+            problems(vec![Move::new(0, 1)]),
+            [(0, Kind::UnknownMove(0), Severity::Error)]
+        );
+        assert_eq!(
+            problems(vec![Move::new(past_the_end, 1)]),
+            [(0, Kind::UnknownMove(past_the_end), Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn duplicates_are_warnings_pointing_at_the_first_occurrence() {
+        let moves = vec![
+            Move::new(TACKLE, 1),
+            Move::new(GROWL, 1),
+            Move::new(TACKLE, 1),
+        ];
+        assert_eq!(
+            problems(moves),
+            [(2, Kind::Duplicate(0), Severity::Warning)]
+        );
+    }
+
+    #[test]
+    fn moves_learned_before_the_previous_one_are_warnings() {
+        let moves = vec![Move::new(TACKLE, 9), Move::new(GROWL, 5)];
+        assert_eq!(
+            problems(moves),
+            [(1, Kind::OutOfOrder { previous_level: 9 }, Severity::Warning)]
+        );
+    }
+
+    #[test]
+    fn problems_name_the_pokemon_and_the_move() {
+        let save_file = SaveFile::from_learnsets(vec![Vec::new(), vec![Move::new(TACKLE, 0)]]);
+        assert_eq!(
+            check(&save_file)[0].to_string(),
+            "Ivysaur (#2), move 1: level 0 is below 1"
+        );
+    }
+
+    #[test]
+    fn warnings_only_fail_strict_checks() {
+        let warnings = check(&SaveFile::from_learnsets(vec![vec![
+            Move::new(TACKLE, 5),
+            Move::new(TACKLE, 5),
+        ]]));
+        let summary = Summary::of(&warnings);
+        assert_eq!(
+            summary,
+            Summary {
+                errors: 0,
+                warnings: 1
+            }
+        );
+        assert!(!summary.fails(false));
+        assert!(summary.fails(true));
+    }
+
+    #[test]
+    fn errors_always_fail() {
+        let errors = check(&SaveFile::from_learnsets(vec![vec![Move::new(0, 5)]]));
+        let summary = Summary::of(&errors);
+        assert!(summary.fails(false));
+        assert!(summary.fails(true));
+    }
+}
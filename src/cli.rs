@@ -0,0 +1,535 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    archives::Archives,
+    egg_moves::{self, EggMoveFile},
+    evolution::{self, EvolutionFile},
+    file::{ParseError, SaveFile},
+    json::ImportError,
+    move_data::{self, MoveDataFile},
+    nds::{self, Rom},
+    patch,
+    personal::{self, PersonalFile},
+};
+
+pub const DEFAULT_OUTPUT: &str = "out.narc";
+const DEFAULT_ROM_OUTPUT: &str = "out.nds";
+
+/// Level-up learnset editor for the generation 5 Pokémon games.
+///
+/// Opens the interactive editor unless a command is given.
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub edit: Option<Edit>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Write the learnsets of an archive as JSON
+    Export {
+        /// Learnset NARC or `.nds` ROM to read
+        input: PathBuf,
+
+        /// Where to write the JSON document [default: standard output]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Overwrite the output file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Build a learnset NARC from a JSON document
+    Import {
+        /// JSON document, in the format written by `export`
+        input: PathBuf,
+
+        /// Where to write the archive [default: out.narc]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Archive or ROM providing the learnsets of the Pokémon missing from the document
+        #[arg(short, long)]
+        base: Option<PathBuf>,
+
+        /// Overwrite the output file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Create IPS and BPS patches turning a file into its edited version
+    Patch {
+        /// Unedited NARC or ROM
+        original: PathBuf,
+
+        /// Edited NARC or ROM
+        modified: PathBuf,
+
+        /// Path of the patches, without extension [default: the modified file's]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Which patches to create
+        #[arg(long, value_enum, default_value_t = PatchFormat::Both)]
+        format: PatchFormat,
+
+        /// Overwrite the patches if they already exist
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Apply an IPS or BPS patch, checking that it produces valid learnsets
+    Apply {
+        /// IPS or BPS patch
+        patch: PathBuf,
+
+        /// Unedited NARC or ROM the patch was made from
+        input: PathBuf,
+
+        /// Where to write the patched file [default: only check the patch]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Overwrite the output file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show which moves were added, removed or re-leveled for each Pokémon
+    Diff {
+        /// Reference NARC or ROM, such as the unedited game
+        old: PathBuf,
+
+        /// Edited NARC or ROM
+        new: PathBuf,
+
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report invalid levels and moves, duplicates and misordered moves, failing on errors
+    Check {
+        /// Learnset NARC or `.nds` ROM to check
+        input: PathBuf,
+
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PatchFormat {
+    Ips,
+    Bps,
+    Both,
+}
+
+#[derive(Args)]
+pub struct Edit {
+    /// Learnset NARC (`a/0/1/8` in the ROM filesystem) or `.nds` ROM to open
+    pub input: PathBuf,
+
+    /// Where to write the edited archive or ROM [default: out.narc or out.nds]
+    #[arg(short, long, conflicts_with = "in_place")]
+    pub output: Option<PathBuf>,
+
+    /// Write the edited archive or ROM back to the input file
+    #[arg(short, long)]
+    pub in_place: bool,
+
+    /// Overwrite the output file if it already exists
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// NARC or ROM to show next to the edited learnsets, such as the unedited game
+    #[arg(short, long)]
+    pub compare: Option<PathBuf>,
+
+    /// Keep learnsets sorted by level after every edit
+    #[arg(long)]
+    pub auto_sort: bool,
+
+    /// Personal data NARC (`a/0/1/6`) to edit along with a learnset NARC, ROMs providing their own
+    #[arg(short, long)]
+    pub personal: Option<PathBuf>,
+
+    /// Egg move NARC (`a/1/2/3` or `a/1/2/4`) to edit along with a learnset NARC, ROMs providing
+    /// their own
+    #[arg(short, long)]
+    pub egg_moves: Option<PathBuf>,
+
+    /// Evolution NARC (`a/0/1/9`) to edit along with a learnset NARC, ROMs providing their own
+    #[arg(long)]
+    pub evolutions: Option<PathBuf>,
+
+    /// Move data NARC (`a/0/2/1`) to edit along with a learnset NARC, ROMs providing their own
+    #[arg(long)]
+    pub move_data: Option<PathBuf>,
+}
+
+/// Archives given on their own to edit along with a learnset NARC.
+pub struct ArchivePaths<'a> {
+    pub personal: Option<&'a Path>,
+    pub egg_moves: Option<&'a Path>,
+    pub evolutions: Option<&'a Path>,
+    pub move_data: Option<&'a Path>,
+}
+
+impl Edit {
+    #[must_use]
+    pub fn output_path(&self) -> &Path {
+        if self.in_place {
+            &self.input
+        } else if let Some(output) = &self.output {
+            output
+        } else if is_rom(&self.input) {
+            Path::new(DEFAULT_ROM_OUTPUT)
+        } else {
+            Path::new(DEFAULT_OUTPUT)
+        }
+    }
+
+    /// Where the archive given with `--personal` is written: next to the output, with a
+    /// `.personal.narc` extension, or back to itself when editing in place.
+    #[must_use]
+    pub fn personal_output(&self) -> Option<PathBuf> {
+        self.archive_output(self.personal.as_deref(), "personal.narc")
+    }
+
+    /// Where the archive given with `--egg-moves` is written, like [`Edit::personal_output`]
+    /// with a `.egg_moves.narc` extension.
+    #[must_use]
+    pub fn egg_moves_output(&self) -> Option<PathBuf> {
+        self.archive_output(self.egg_moves.as_deref(), "egg_moves.narc")
+    }
+
+    /// Where the archive given with `--evolutions` is written, like [`Edit::personal_output`]
+    /// with a `.evolutions.narc` extension.
+    #[must_use]
+    pub fn evolutions_output(&self) -> Option<PathBuf> {
+        self.archive_output(self.evolutions.as_deref(), "evolutions.narc")
+    }
+
+    /// Where the archive given with `--move-data` is written, like [`Edit::personal_output`]
+    /// with a `.move_data.narc` extension.
+    #[must_use]
+    pub fn move_data_output(&self) -> Option<PathBuf> {
+        self.archive_output(self.move_data.as_deref(), "move_data.narc")
+    }
+
+    #[must_use]
+    pub fn archive_paths(&self) -> ArchivePaths<'_> {
+        ArchivePaths {
+            personal: self.personal.as_deref(),
+            egg_moves: self.egg_moves.as_deref(),
+            evolutions: self.evolutions.as_deref(),
+            move_data: self.move_data.as_deref(),
+        }
+    }
+
+    fn archive_output(&self, archive: Option<&Path>, extension: &str) -> Option<PathBuf> {
+        let archive = archive.filter(|_| !is_rom(&self.input))?;
+        Some(if self.in_place {
+            archive.to_path_buf()
+        } else {
+            self.output_path().with_extension(extension)
+        })
+    }
+
+    /// Fails early when an output would be overwritten without the user asking for it.
+    pub fn check_output(&self) -> Result<(), Error> {
+        if self.in_place || self.force {
+            return Ok(());
+        }
+        let personal_output = self.personal_output();
+        let egg_moves_output = self.egg_moves_output();
+        let evolutions_output = self.evolutions_output();
+        let move_data_output = self.move_data_output();
+        let outputs = [
+            Some(self.output_path()),
+            personal_output.as_deref(),
+            egg_moves_output.as_deref(),
+            evolutions_output.as_deref(),
+            move_data_output.as_deref(),
+        ];
+        match outputs.into_iter().flatten().find(|output| output.exists()) {
+            Some(output) => Err(Error::OutputExists(output.to_path_buf())),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the edited archives, refusing to replace an existing file unless allowed to.
+    ///
+    /// `overwrite` allows replacing a file previously written by this same session.
+    pub fn write_output(
+        &self,
+        container: &Container,
+        save_file: &SaveFile,
+        archives: &Archives,
+        overwrite: bool,
+    ) -> Result<(), Error> {
+        let overwrite = overwrite || self.in_place || self.force;
+        write(
+            self.output_path(),
+            &container.to_binary_format(save_file, archives)?,
+            overwrite,
+        )?;
+        if let (Some(output), Some(personal)) = (self.personal_output(), &archives.personal) {
+            write(&output, &personal.to_binary_format(), overwrite)?;
+        }
+        if let (Some(output), Some(egg_moves)) = (self.egg_moves_output(), &archives.egg_moves) {
+            write(&output, &egg_moves.to_binary_format(), overwrite)?;
+        }
+        if let (Some(output), Some(evolutions)) = (self.evolutions_output(), &archives.evolutions) {
+            write(&output, &evolutions.to_binary_format(), overwrite)?;
+        }
+        if let (Some(output), Some(move_data)) = (self.move_data_output(), &archives.move_data) {
+            write(&output, &move_data.to_binary_format(), overwrite)?;
+        }
+        Ok(())
+    }
+}
+
+/// File the learnsets were loaded from, which decides how they are written back.
+pub enum Container {
+    Narc,
+    /// The whole ROM image, in which the learnset NARC gets replaced.
+    Rom(PathBuf, Vec<u8>),
+}
+
+impl Container {
+    /// Loads the learnsets from a NARC, or from the NARC inside a ROM for `.nds` files.
+    pub fn open(path: &Path) -> Result<(Self, SaveFile), Error> {
+        Self::from_bytes(path, read(path)?)
+    }
+
+    /// Same as [`Container::open`] for a file already read, `path` deciding how to parse it.
+    pub fn from_bytes(path: &Path, bytes: Vec<u8>) -> Result<(Self, SaveFile), Error> {
+        if !is_rom(path) {
+            let save_file = SaveFile::try_from(bytes.as_ref())
+                .map_err(|error| Error::Parse(path.to_path_buf(), error))?;
+            return Ok((Self::Narc, save_file));
+        }
+
+        let rom_error = |error| Error::Rom(path.to_path_buf(), error);
+        let rom = Rom::try_from(bytes.as_ref()).map_err(rom_error)?;
+        let narc = rom.file(rom.game().learnset_path()).map_err(rom_error)?;
+        let save_file =
+            SaveFile::try_from(narc).map_err(|error| Error::Parse(path.to_path_buf(), error))?;
+        Ok((Self::Rom(path.to_path_buf(), bytes), save_file))
+    }
+
+    /// Loads the other archives from the ROM, or from the given `paths` for a learnset NARC.
+    pub fn open_archives(&self, paths: &ArchivePaths) -> Result<Archives, Error> {
+        let parse_personal = |path: &Path, bytes: &[u8]| {
+            PersonalFile::try_from(bytes)
+                .map_err(|error| Error::Personal(path.to_path_buf(), error))
+        };
+        let parse_egg_moves = |path: &Path, bytes: &[u8]| {
+            EggMoveFile::try_from(bytes).map_err(|error| Error::EggMoves(path.to_path_buf(), error))
+        };
+        let parse_evolutions = |path: &Path, bytes: &[u8]| {
+            EvolutionFile::try_from(bytes)
+                .map_err(|error| Error::Evolutions(path.to_path_buf(), error))
+        };
+        let parse_move_data = |path: &Path, bytes: &[u8]| {
+            MoveDataFile::try_from(bytes)
+                .map_err(|error| Error::MoveData(path.to_path_buf(), error))
+        };
+        match self {
+            Self::Narc => Ok(Archives {
+                personal: paths
+                    .personal
+                    .map(|path| parse_personal(path, &read(path)?))
+                    .transpose()?,
+                egg_moves: paths
+                    .egg_moves
+                    .map(|path| parse_egg_moves(path, &read(path)?))
+                    .transpose()?,
+                evolutions: paths
+                    .evolutions
+                    .map(|path| parse_evolutions(path, &read(path)?))
+                    .transpose()?,
+                move_data: paths
+                    .move_data
+                    .map(|path| parse_move_data(path, &read(path)?))
+                    .transpose()?,
+            }),
+            Self::Rom(path, bytes) => {
+                let rom_error = |error| Error::Rom(path.clone(), error);
+                let rom = Rom::try_from(bytes.as_ref()).map_err(rom_error)?;
+                let game = rom.game();
+                let personal = rom.file(game.personal_path()).map_err(rom_error)?;
+                let egg_moves = rom.file(game.egg_move_path()).map_err(rom_error)?;
+                let evolutions = rom.file(game.evolution_path()).map_err(rom_error)?;
+                let move_data = rom.file(game.move_data_path()).map_err(rom_error)?;
+                Ok(Archives {
+                    personal: Some(parse_personal(path, personal)?),
+                    egg_moves: Some(parse_egg_moves(path, egg_moves)?),
+                    evolutions: Some(parse_evolutions(path, evolutions)?),
+                    move_data: Some(parse_move_data(path, move_data)?),
+                })
+            }
+        }
+    }
+
+    /// Bytes of the whole file to write, with `save_file` in place of the original learnsets.
+    ///
+    /// The `archives` are written into the ROM as well, but not into a learnset NARC.
+    pub fn to_binary_format(
+        &self,
+        save_file: &SaveFile,
+        archives: &Archives,
+    ) -> Result<Vec<u8>, Error> {
+        let narc = save_file.to_binary_format();
+        match self {
+            Self::Narc => Ok(narc),
+            Self::Rom(path, bytes) => {
+                let rom_error = |error| Error::Rom(path.clone(), error);
+                let rom = Rom::try_from(bytes.as_ref()).map_err(rom_error)?;
+                let game = rom.game();
+                let mut bytes = rom
+                    .with_file(game.learnset_path(), &narc)
+                    .map_err(rom_error)?;
+                let files = [
+                    (
+                        game.personal_path(),
+                        archives
+                            .personal
+                            .as_ref()
+                            .map(PersonalFile::to_binary_format),
+                    ),
+                    (
+                        game.egg_move_path(),
+                        archives
+                            .egg_moves
+                            .as_ref()
+                            .map(EggMoveFile::to_binary_format),
+                    ),
+                    (
+                        game.evolution_path(),
+                        archives
+                            .evolutions
+                            .as_ref()
+                            .map(EvolutionFile::to_binary_format),
+                    ),
+                    (
+                        game.move_data_path(),
+                        archives
+                            .move_data
+                            .as_ref()
+                            .map(MoveDataFile::to_binary_format),
+                    ),
+                ];
+                for (path, file) in files {
+                    if let Some(file) = file {
+                        let rom = Rom::try_from(bytes.as_ref()).map_err(rom_error)?;
+                        bytes = rom.with_file(path, &file).map_err(rom_error)?;
+                    }
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+pub fn load(path: &Path) -> Result<SaveFile, Error> {
+    Container::open(path).map(|(_, save_file)| save_file)
+}
+
+fn is_rom(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("nds"))
+}
+
+pub fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|source| Error::Read(path.to_path_buf(), source))
+}
+
+/// Writes `bytes` to `path`, failing if it already exists unless `overwrite` is set.
+pub fn write(path: &Path, bytes: &[u8], overwrite: bool) -> Result<(), Error> {
+    let written = if overwrite {
+        fs::write(path, bytes)
+    } else {
+        fs::File::create_new(path).and_then(|mut file| io::Write::write_all(&mut file, bytes))
+    };
+    written.map_err(|source| match source.kind() {
+        io::ErrorKind::AlreadyExists => Error::OutputExists(path.to_path_buf()),
+        _ => Error::Write(path.to_path_buf(), source),
+    })
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+    Personal(PathBuf, personal::ParseError),
+    EggMoves(PathBuf, egg_moves::ParseError),
+    Evolutions(PathBuf, evolution::ParseError),
+    MoveData(PathBuf, move_data::ParseError),
+    Rom(PathBuf, nds::Error),
+    Import(PathBuf, ImportError),
+    Patch(PathBuf, patch::Error),
+    OutputExists(PathBuf),
+    Write(PathBuf, io::Error),
+    Check {
+        path: PathBuf,
+        errors: usize,
+        warnings: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(path, source) => write!(f, "cannot read {}: {source}", path.display()),
+            Self::Parse(path, error) => write!(f, "cannot parse {}: {error}", path.display()),
+            Self::Personal(path, error) => write!(
+                f,
+                "cannot parse the personal data of {}: {error}",
+                path.display()
+            ),
+            Self::EggMoves(path, error) => write!(
+                f,
+                "cannot parse the egg moves of {}: {error}",
+                path.display()
+            ),
+            Self::Evolutions(path, error) => write!(
+                f,
+                "cannot parse the evolutions of {}: {error}",
+                path.display()
+            ),
+            Self::MoveData(path, error) => write!(
+                f,
+                "cannot parse the move data of {}: {error}",
+                path.display()
+            ),
+            Self::Rom(path, error) => write!(f, "cannot read ROM {}: {error}", path.display()),
+            Self::Patch(path, error) => write!(f, "cannot patch with {}: {error}", path.display()),
+            Self::Import(path, error) => write!(f, "cannot import {}: {error}", path.display()),
+            Self::OutputExists(path) => write!(
+                f,
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            ),
+            Self::Write(path, source) => write!(f, "cannot write {}: {source}", path.display()),
+            Self::Check {
+                path,
+                errors,
+                warnings,
+            } => write!(
+                f,
+                "{} has {errors} errors and {warnings} warnings",
+                path.display()
+            ),
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Comparison of learnsets, move by move.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    file::{Move, SaveFile},
+    names::POKE_NAMES,
+};
+
+/// How a move of a learnset differs between two versions of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Added {
+        id: u16,
+        name: &'static str,
+        level: u16,
+    },
+    Removed {
+        id: u16,
+        name: &'static str,
+        level: u16,
+    },
+    Releveled {
+        id: u16,
+        name: &'static str,
+        old_level: u16,
+        new_level: u16,
+    },
+}
+
+impl Change {
+    const fn sort_key(&self) -> (u16, u16) {
+        match *self {
+            Self::Added { id, level, .. } | Self::Removed { id, level, .. } => (level, id),
+            Self::Releveled { id, new_level, .. } => (new_level, id),
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { name, level, .. } => write!(f, "+ {name} at level {level}"),
+            Self::Removed { name, level, .. } => write!(f, "- {name} at level {level}"),
+            Self::Releveled {
+                name,
+                old_level,
+                new_level,
+                ..
+            } => write!(f, "~ {name} from level {old_level} to {new_level}"),
+        }
+    }
+}
+
+/// What happened to one move of a learnset, for showing both versions next to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Unchanged,
+    Added,
+    Removed,
+    Releveled,
+}
+
+/// Changes of one Pokémon's learnset.
+#[derive(Serialize)]
+pub struct PokemonDiff {
+    /// National index, starting at 1 like in the editor.
+    pub index: usize,
+    /// Name of the Pokémon, omitted for members past the known Pokémon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    pub changes: Vec<Change>,
+}
+
+/// Lists the changes between two learnsets, ordered by level.
+///
+/// Occurrences of a move learned at the same level on both sides are unchanged. The remaining
+/// occurrences are paired in level order as level changes, and the leftovers are additions or
+/// removals.
+#[must_use]
+pub fn learnsets(old: &[Move], new: &[Move]) -> Vec<Change> {
+    let mut ids: Vec<u16> = old.iter().chain(new).map(|m| m.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut changes = Vec::new();
+    for id in ids {
+        let levels = |moves: &[Move]| {
+            let mut levels: Vec<u16> = moves
+                .iter()
+                .filter(|m| m.id == id)
+                .map(|m| m.level)
+                .collect();
+            levels.sort_unstable();
+            levels
+        };
+        let mut old_levels = levels(old);
+        let mut new_levels = levels(new);
+        old_levels.retain(|level| {
+            let unchanged = new_levels.iter().position(|new_level| new_level == level);
+            unchanged.map(|index| new_levels.remove(index)).is_none()
+        });
+
+        let name = Move::name_of(id);
+        let mut old_levels = old_levels.into_iter();
+        let mut new_levels = new_levels.into_iter();
+        loop {
+            changes.push(match (old_levels.next(), new_levels.next()) {
+                (Some(old_level), Some(new_level)) => Change::Releveled {
+                    id,
+                    name,
+                    old_level,
+                    new_level,
+                },
+                (Some(level), None) => Change::Removed { id, name, level },
+                (None, Some(level)) => Change::Added { id, name, level },
+                (None, None) => break,
+            });
+        }
+    }
+    changes.sort_by_key(Change::sort_key);
+    changes
+}
+
+/// Status of each move of `old` and of `new`, following the pairing of [`learnsets`].
+#[must_use]
+pub fn statuses(old: &[Move], new: &[Move]) -> (Vec<Status>, Vec<Status>) {
+    let mut old_statuses = vec![Status::Unchanged; old.len()];
+    let mut new_statuses = vec![Status::Unchanged; new.len()];
+    let mark = |moves: &[Move], statuses: &mut [Status], id, level, status| {
+        let index = (0..moves.len()).find(|&index| {
+            statuses[index] == Status::Unchanged && moves[index] == Move::new(id, level)
+        });
+        if let Some(index) = index {
+            statuses[index] = status;
+        }
+    };
+    for change in learnsets(old, new) {
+        match change {
+            Change::Added { id, level, .. } => {
+                mark(new, &mut new_statuses, id, level, Status::Added);
+            }
+            Change::Removed { id, level, .. } => {
+                mark(old, &mut old_statuses, id, level, Status::Removed);
+            }
+            Change::Releveled {
+                id,
+                old_level,
+                new_level,
+                ..
+            } => {
+                mark(old, &mut old_statuses, id, old_level, Status::Releveled);
+                mark(new, &mut new_statuses, id, new_level, Status::Releveled);
+            }
+        }
+    }
+    (old_statuses, new_statuses)
+}
+
+/// Lists the Pokémon whose learnset differs, a learnset missing on one side counting as empty.
+#[must_use]
+pub fn save_files(old: &SaveFile, new: &SaveFile) -> Vec<PokemonDiff> {
+    let count = old.pokemons.len().max(new.pokemons.len());
+    (0..count)
+        .filter_map(|index| {
+            let changes = learnsets(learnset(old, index), learnset(new, index));
+            (!changes.is_empty()).then(|| PokemonDiff {
+                index: index + 1,
+                name: POKE_NAMES.get(index).copied(),
+                changes,
+            })
+        })
+        .collect()
+}
+
+fn learnset(save_file: &SaveFile, index: usize) -> &[Move] {
+    save_file.pokemons.get(index).map_or(&[], Vec::as_slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TACKLE: u16 = 33;
+    const GROWL: u16 = 45;
+    const VINE_WHIP: u16 = 22;
+
+    fn learnset() -> Vec<Move> {
+        vec![
+            Move::new(TACKLE, 1),
+            Move::new(GROWL, 3),
+            Move::new(VINE_WHIP, 9),
+        ]
+    }
+
+    #[test]
+    fn identical_learnsets_have_no_changes() {
+        assert!(learnsets(&learnset(), &learnset()).is_empty());
+        assert!(learnsets(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn moves_only_on_one_side_are_added_or_removed() {
+        let mut new = learnset();
+        new.remove(1);
+        new.push(Move::new(GROWL, 13));
+        new.push(Move::new(1, 20));
+
+        assert_eq!(
+            learnsets(&learnset(), &new),
+            [
+                Change::Releveled {
+                    id: GROWL,
+                    name: "Growl",
+                    old_level: 3,
+                    new_level: 13,
+                },
+                Change::Added {
+                    id: 1,
+                    name: "Pound",
+                    level: 20,
+                },
+            ]
+        );
+        assert_eq!(
+            learnsets(&learnset(), &learnset()[..2]),
+            [Change::Removed {
+                id: VINE_WHIP,
+                name: "Vine Whip",
+                level: 9,
+            }]
+        );
+    }
+
+    #[test]
+    fn replaced_moves_are_a_removal_and_an_addition() {
+        let mut new = learnset();
+        new[1] = Move::new(1, 3);
+
+        assert_eq!(
+            learnsets(&learnset(), &new),
+            [
+                Change::Added {
+                    id: 1,
+                    name: "Pound",
+                    level: 3,
+                },
+                Change::Removed {
+                    id: GROWL,
+                    name: "Growl",
+                    level: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn occurrences_learned_at_the_same_level_are_unchanged() {
+        let old = [Move::new(TACKLE, 5), Move::new(TACKLE, 10)];
+        let new = [Move::new(TACKLE, 10), Move::new(TACKLE, 20)];
+
+        assert_eq!(
+            learnsets(&old, &new),
+            [Change::Releveled {
+                id: TACKLE,
+                name: "Tackle",
+                old_level: 5,
+                new_level: 20,
+            }]
+        );
+    }
+
+    #[test]
+    fn statuses_mark_each_side_of_a_change() {
+        let mut new = learnset();
+        new[0].level = 4;
+        new[2] = Move::new(1, 9);
+        let (old_statuses, new_statuses) = statuses(&learnset(), &new);
+
+        assert_eq!(
+            old_statuses,
+            [Status::Releveled, Status::Unchanged, Status::Removed]
+        );
+        assert_eq!(
+            new_statuses,
+            [Status::Releveled, Status::Unchanged, Status::Added]
+        );
+    }
+
+    #[test]
+    fn statuses_of_identical_learnsets_are_unchanged() {
+        let (old_statuses, new_statuses) = statuses(&learnset(), &learnset());
+        assert!(
+            old_statuses
+                .iter()
+                .chain(&new_statuses)
+                .all(|&status| status == Status::Unchanged)
+        );
+    }
+
+    #[test]
+    fn only_differing_pokemon_are_listed() {
+        let old = SaveFile::from_learnsets(vec![learnset(), learnset(), learnset()]);
+        let mut new = SaveFile::from_learnsets(old.pokemons.clone());
+        new.pokemons[1].pop();
+
+        let diffs = save_files(&old, &new);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].index, diffs[0].name), (2, Some("Ivysaur")));
+        assert!(save_files(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn pokemon_missing_from_one_file_count_as_empty() {
+        let count = POKE_NAMES.len() + 1;
+        let old = SaveFile::from_learnsets(vec![learnset(); count - 1]);
+        let new = SaveFile::from_learnsets(vec![learnset(); count]);
+
+        let added = save_files(&old, &new);
+        assert_eq!(added.len(), 1);
+        assert_eq!((added[0].index, added[0].name), (count, None));
+        assert_eq!(added[0].changes.len(), learnset().len());
+
+        let removed = save_files(&new, &old);
+        assert!(
+            removed[0]
+                .changes
+                .iter()
+                .all(|change| matches!(change, Change::Removed { .. }))
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Egg move archive (`a/1/2/3` in Black and White, `a/1/2/4` in Black 2 and White 2): moves
+//! passed down by breeding, indexed like [`POKE_NAMES`](crate::names::POKE_NAMES).
+
+use std::fmt::Display;
+
+use crate::{
+    file::pokemon_name,
+    narc::{ArchiveError, Envelope},
+};
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct EggMoveFile {
+    envelope: Envelope,
+    /// Move ids of each Pokémon.
+    pub pokemons: Vec<Vec<u16>>,
+    /// Bytes following the list of each Pokémon, such as padding, written back as is.
+    trailers: Vec<Box<[u8]>>,
+}
+
+impl EggMoveFile {
+    #[must_use]
+    pub fn to_binary_format(&self) -> Vec<u8> {
+        let files: Vec<_> = self
+            .pokemons
+            .iter()
+            .zip(&self.trailers)
+            .map(|(moves, trailer)| egg_moves_to_binary_format(moves, trailer))
+            .collect();
+        self.envelope.to_binary_format(&files)
+    }
+}
+
+impl TryFrom<&[u8]> for EggMoveFile {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (envelope, members) = Envelope::split(value).map_err(ParseError::Archive)?;
+        let (pokemons, trailers) = members
+            .iter()
+            .enumerate()
+            .map(|(pokemon, member)| parse_egg_moves(pokemon, member.offset, member.data))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            envelope,
+            pokemons,
+            trailers,
+        })
+    }
+}
+
+/// Reasons an egg move archive cannot be read.
+#[derive(Debug)]
+pub enum ParseError {
+    Archive(ArchiveError),
+    Truncated {
+        pokemon: usize,
+        offset: usize,
+        count: Option<u16>,
+        len: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::Truncated {
+                pokemon,
+                offset,
+                count: None,
+                len,
+            } => write!(
+                f,
+                "egg moves of {} at offset {offset:#x} are {len} bytes long, \
+                 too short for the move count",
+                pokemon_name(*pokemon)
+            ),
+            Self::Truncated {
+                pokemon,
+                offset,
+                count: Some(count),
+                len,
+            } => write!(
+                f,
+                "egg moves of {} at offset {offset:#x} are {len} bytes long, \
+                 too short for {count} moves",
+                pokemon_name(*pokemon)
+            ),
+        }
+    }
+}
+
+fn egg_moves_to_binary_format(moves: &[u16], trailer: &[u8]) -> Vec<u8> {
+    let count = u16::try_from(moves.len()).expect("a Pokémon has at most 65535 egg moves");
+    let mut result = Vec::with_capacity((moves.len() + 1) * 2 + trailer.len());
+    result.extend_from_slice(&count.to_le_bytes());
+    for id in moves {
+        result.extend_from_slice(&id.to_le_bytes());
+    }
+    result.extend_from_slice(trailer);
+    result
+}
+
+/// Parses a single egg move member file: a move count followed by as many move ids.
+fn parse_egg_moves(
+    pokemon: usize,
+    offset: usize,
+    data: &[u8],
+) -> Result<(Vec<u16>, Box<[u8]>), ParseError> {
+    let truncated = |count| ParseError::Truncated {
+        pokemon,
+        offset,
+        count,
+        len: data.len(),
+    };
+    let (count, data) = data.split_first_chunk::<2>().ok_or(truncated(None))?;
+    let count = u16::from_le_bytes(*count);
+    let (moves, trailer) = data
+        .split_at_checked(usize::from(count) * 2)
+        .ok_or(truncated(Some(count)))?;
+    let moves = moves
+        .as_chunks::<2>()
+        .0
+        .iter()
+        .map(|&id| u16::from_le_bytes(id))
+        .collect();
+    Ok((moves, Box::from(trailer)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TACKLE: u16 = 33;
+    const GROWL: u16 = 45;
+
+    #[test]
+    fn moves_are_read_after_their_count() {
+        let data = Envelope::unnamed(&[0, 0])
+            .to_binary_format(&[vec![2, 0, 0x21, 0, 0x2d, 0], vec![0, 0]]);
+        let egg_moves = EggMoveFile::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(egg_moves.pokemons, [vec![TACKLE, GROWL], vec![]]);
+    }
+
+    #[test]
+    fn trailing_bytes_are_written_back() {
+        let data = Envelope::unnamed(&[0, 0])
+            .to_binary_format(&[vec![1, 0, 0x21, 0, 0xaa, 0xbb], vec![0, 0, 0xcc]]);
+        let mut egg_moves = EggMoveFile::try_from(data.as_slice()).unwrap();
+        assert_eq!(egg_moves.to_binary_format(), data);
+
+        egg_moves.pokemons[0].push(GROWL);
+        let edited = EggMoveFile::try_from(egg_moves.to_binary_format().as_slice()).unwrap();
+        assert_eq!(edited.pokemons[0], [TACKLE, GROWL]);
+        assert_eq!(*edited.trailers[0], [0xaa, 0xbb]);
+        assert_eq!(*edited.trailers[1], [0xcc]);
+    }
+
+    #[test]
+    fn members_too_short_for_their_moves_are_reported() {
+        let data = Envelope::unnamed(&[]).to_binary_format(&[vec![0, 0], vec![3, 0, 0x21, 0]]);
+        let (_, members) = Envelope::split(&data).unwrap();
+        assert!(matches!(
+            EggMoveFile::try_from(data.as_slice()),
+            Err(ParseError::Truncated { pokemon: 1, offset, count: Some(3), len: 4 })
+                if offset == members[1].offset
+        ));
+
+        let data = Envelope::unnamed(&[]).to_binary_format(&[vec![0x21]]);
+        assert!(matches!(
+            EggMoveFile::try_from(data.as_slice()),
+            Err(ParseError::Truncated {
+                pokemon: 0,
+                count: None,
+                len: 1,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,420 @@
+//! Evolution archive (`a/0/1/9`): how and into what each Pokémon evolves, indexed like
+//! [`POKE_NAMES`](crate::names::POKE_NAMES).
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{
+    file::{MAX_LEVEL, MIN_LEVEL, Move, pokemon_name},
+    input::parse_in_range,
+    names::{MOVE_NAMES, POKE_NAMES},
+    narc::{ArchiveError, Envelope},
+    personal::MAX_ITEM,
+};
+
+/// Evolutions each Pokémon has room for.
+pub const EVOLUTION_COUNT: usize = 7;
+const EVOLUTION_LEN: usize = 6;
+/// Highest national number, forms having no number of their own.
+const MAX_SPECIES: u16 = 649;
+
+/// Evolution methods, indexed by their id.
+pub const METHOD_NAMES: [&str; 28] = [
+    "None",
+    "Friendship",
+    "Friendship, day",
+    "Friendship, night",
+    "Level",
+    "Trade",
+    "Trade holding item",
+    "Trade with Pokémon",
+    "Use item",
+    "Level, Attack > Defense",
+    "Level, Attack = Defense",
+    "Level, Attack < Defense",
+    "Level, personality low",
+    "Level, personality high",
+    "Level, Ninjask",
+    "Level, Shedinja",
+    "Beauty",
+    "Use item, male",
+    "Use item, female",
+    "Hold item, day",
+    "Hold item, night",
+    "Knowing move",
+    "With Pokémon in party",
+    "Level, male",
+    "Level, female",
+    "Level, magnetic field",
+    "Level, Moss Rock",
+    "Level, Ice Rock",
+];
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct EvolutionFile {
+    envelope: Envelope,
+    pub pokemons: Vec<[Evolution; EVOLUTION_COUNT]>,
+    /// Bytes following the evolutions of each Pokémon, such as padding, written back as is.
+    trailers: Vec<Box<[u8]>>,
+}
+
+impl EvolutionFile {
+    #[must_use]
+    pub fn to_binary_format(&self) -> Vec<u8> {
+        let files: Vec<_> = self
+            .pokemons
+            .iter()
+            .zip(&self.trailers)
+            .map(|(evolutions, trailer)| {
+                let mut file: Vec<u8> = evolutions
+                    .iter()
+                    .flat_map(|&evolution| <[u8; EVOLUTION_LEN]>::from(evolution))
+                    .collect();
+                file.extend_from_slice(trailer);
+                file
+            })
+            .collect();
+        self.envelope.to_binary_format(&files)
+    }
+}
+
+impl TryFrom<&[u8]> for EvolutionFile {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (envelope, members) = Envelope::split(value).map_err(ParseError::Archive)?;
+        let (pokemons, trailers) = members
+            .iter()
+            .enumerate()
+            .map(|(pokemon, member)| {
+                let (evolutions, trailer) = member
+                    .data
+                    .split_first_chunk::<{ EVOLUTION_COUNT * EVOLUTION_LEN }>()
+                    .ok_or(ParseError::ShortEntry {
+                        pokemon,
+                        offset: member.offset,
+                        len: member.data.len(),
+                    })?;
+                let (evolutions, _) = evolutions.as_chunks::<EVOLUTION_LEN>();
+                let evolutions = std::array::from_fn(|index| Evolution::from(&evolutions[index]));
+                Ok((evolutions, Box::from(trailer)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            envelope,
+            pokemons,
+            trailers,
+        })
+    }
+}
+
+/// Reasons an evolution archive cannot be read.
+#[derive(Debug)]
+pub enum ParseError {
+    Archive(ArchiveError),
+    ShortEntry {
+        pokemon: usize,
+        offset: usize,
+        len: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::ShortEntry {
+                pokemon,
+                offset,
+                len,
+            } => write!(
+                f,
+                "evolutions of {} at offset {offset:#x} are {len} bytes long, {} were expected",
+                pokemon_name(*pokemon),
+                EVOLUTION_COUNT * EVOLUTION_LEN
+            ),
+        }
+    }
+}
+
+/// One way a Pokémon evolves, unused when its method is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Evolution {
+    pub method: u16,
+    pub parameter: u16,
+    /// National number of the Pokémon evolved into.
+    pub target: u16,
+}
+
+impl Evolution {
+    #[must_use]
+    pub fn method_name(&self) -> String {
+        METHOD_NAMES
+            .get(usize::from(self.method))
+            .map_or_else(|| format!("Unknown ({})", self.method), ToString::to_string)
+    }
+
+    #[must_use]
+    pub const fn parameter_kind(&self) -> Parameter {
+        match self.method {
+            4 | 9..=15 | 23 | 24 => Parameter::Level,
+            6 | 8 | 17..=20 => Parameter::Item,
+            16 => Parameter::Beauty,
+            21 => Parameter::Move,
+            22 => Parameter::Species,
+            _ => Parameter::None,
+        }
+    }
+
+    /// The parameter as shown to the user: a level, an item, a move or a Pokémon.
+    #[must_use]
+    pub fn parameter_name(&self) -> String {
+        match self.parameter_kind() {
+            Parameter::None => String::new(),
+            Parameter::Level => format!("Level {}", self.parameter),
+            Parameter::Item => format!("Item #{}", self.parameter),
+            Parameter::Beauty => format!("Beauty {}", self.parameter),
+            Parameter::Move => Move::name_of(self.parameter).to_string(),
+            Parameter::Species => species_name(self.parameter),
+        }
+    }
+
+    #[must_use]
+    pub fn target_name(&self) -> String {
+        species_name(self.target)
+    }
+}
+
+impl From<&[u8; EVOLUTION_LEN]> for Evolution {
+    fn from(value: &[u8; EVOLUTION_LEN]) -> Self {
+        Self {
+            method: u16::from_le_bytes([value[0], value[1]]),
+            parameter: u16::from_le_bytes([value[2], value[3]]),
+            target: u16::from_le_bytes([value[4], value[5]]),
+        }
+    }
+}
+
+impl From<Evolution> for [u8; EVOLUTION_LEN] {
+    fn from(value: Evolution) -> Self {
+        let [method, parameter, target] =
+            [value.method, value.parameter, value.target].map(u16::to_le_bytes);
+        [
+            method[0],
+            method[1],
+            parameter[0],
+            parameter[1],
+            target[0],
+            target[1],
+        ]
+    }
+}
+
+/// What the parameter of an evolution method is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    None,
+    Level,
+    Item,
+    Beauty,
+    Move,
+    Species,
+}
+
+impl Parameter {
+    /// Values the games handle for this parameter.
+    #[must_use]
+    pub const fn range(self) -> RangeInclusive<u16> {
+        match self {
+            Self::None => 0..=0,
+            Self::Level => MIN_LEVEL..=MAX_LEVEL,
+            Self::Item => 1..=MAX_ITEM,
+            Self::Beauty => 0..=255,
+            Self::Move => 1..=MOVE_NAMES.len() as u16,
+            Self::Species => 1..=MAX_SPECIES,
+        }
+    }
+}
+
+/// Part of an evolution edited on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    Method,
+    Parameter,
+    Target,
+}
+
+impl Part {
+    pub const ALL: [Self; 3] = [Self::Method, Self::Parameter, Self::Target];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Method => "Method",
+            Self::Parameter => "Parameter",
+            Self::Target => "Target",
+        }
+    }
+
+    /// Values this part can take in `evolution`.
+    #[must_use]
+    pub const fn range(self, evolution: &Evolution) -> RangeInclusive<u16> {
+        match self {
+            Self::Method => 0..=METHOD_NAMES.len() as u16 - 1,
+            Self::Parameter => evolution.parameter_kind().range(),
+            Self::Target => 1..=MAX_SPECIES,
+        }
+    }
+
+    /// Parses a value typed by the user for this part of `evolution`, checking its range.
+    pub fn parse(self, evolution: &Evolution, input: &str) -> Result<u16, String> {
+        parse_in_range(self.name(), &self.range(evolution), input)
+    }
+
+    #[must_use]
+    pub const fn get(self, evolution: &Evolution) -> u16 {
+        match self {
+            Self::Method => evolution.method,
+            Self::Parameter => evolution.parameter,
+            Self::Target => evolution.target,
+        }
+    }
+
+    /// Returns `evolution` with this part set to `value`. A new method clears a parameter of
+    /// another kind, and no method clears the whole evolution.
+    #[must_use]
+    pub fn with(self, evolution: Evolution, value: u16) -> Evolution {
+        match self {
+            Self::Method if value == 0 => Evolution::default(),
+            Self::Method => {
+                let mut new = Evolution {
+                    method: value,
+                    ..evolution
+                };
+                if new.parameter_kind() != evolution.parameter_kind() {
+                    new.parameter = 0;
+                }
+                new
+            }
+            Self::Parameter => Evolution {
+                parameter: value,
+                ..evolution
+            },
+            Self::Target => Evolution {
+                target: value,
+                ..evolution
+            },
+        }
+    }
+}
+
+/// Name of the Pokémon with national number `species`, or nothing for 0.
+fn species_name(species: u16) -> String {
+    match species {
+        0 => String::new(),
+        species => POKE_NAMES
+            .get(usize::from(species) - 1)
+            .map_or_else(|| format!("#{species}"), ToString::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: u16 = 4;
+    const USE_ITEM: u16 = 8;
+    const KNOWING_MOVE: u16 = 21;
+
+    /// Member holding Bulbasaur's evolution into Ivysaur at level 16, followed by `trailer`.
+    fn bulbasaur(trailer: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; EVOLUTION_COUNT * EVOLUTION_LEN];
+        data[..EVOLUTION_LEN].copy_from_slice(&[4, 0, 16, 0, 2, 0]);
+        data.extend_from_slice(trailer);
+        data
+    }
+
+    #[test]
+    fn evolutions_are_written_back_with_their_trailing_bytes() {
+        let mut last = bulbasaur(&[0xaa, 0xbb]);
+        last[EVOLUTION_LEN * 6..EVOLUTION_LEN * 7].copy_from_slice(&[8, 0, 0x52, 0, 3, 0]);
+        let data = Envelope::unnamed(&[0; 42]).to_binary_format(&[bulbasaur(&[]), last]);
+        let evolutions = EvolutionFile::try_from(data.as_slice()).unwrap();
+
+        let bulbasaur = Evolution {
+            method: LEVEL,
+            parameter: 16,
+            target: 2,
+        };
+        assert_eq!(evolutions.pokemons[0][0], bulbasaur);
+        assert!(
+            evolutions.pokemons[0][1..]
+                .iter()
+                .all(|&e| e == Evolution::default())
+        );
+        assert_eq!(
+            evolutions.pokemons[1][6],
+            Evolution {
+                method: USE_ITEM,
+                parameter: 0x52,
+                target: 3,
+            }
+        );
+        assert_eq!(evolutions.to_binary_format(), data);
+    }
+
+    #[test]
+    fn short_entries_are_reported_at_their_offset() {
+        let mut short = bulbasaur(&[]);
+        short.pop();
+        let data = Envelope::unnamed(&[]).to_binary_format(&[bulbasaur(&[]), short]);
+        let (_, members) = Envelope::split(&data).unwrap();
+
+        assert!(matches!(
+            EvolutionFile::try_from(data.as_slice()),
+            Err(ParseError::ShortEntry { pokemon: 1, offset, len: 41 })
+                if offset == members[1].offset
+        ));
+    }
+
+    #[test]
+    fn parameters_are_checked_against_the_range_of_the_method() {
+        let with_method = |method| Evolution {
+            method,
+            ..Evolution::default()
+        };
+        let level = with_method(LEVEL);
+        assert_eq!(Part::Parameter.parse(&level, "100"), Ok(100));
+        assert!(Part::Parameter.parse(&level, "0").is_err());
+        assert!(Part::Parameter.parse(&level, "101").is_err());
+
+        let item = with_method(USE_ITEM);
+        assert_eq!(Part::Parameter.parse(&item, "101"), Ok(101));
+        assert!(
+            Part::Parameter
+                .parse(&item, &(MAX_ITEM + 1).to_string())
+                .is_err()
+        );
+
+        let knowing_move = with_method(KNOWING_MOVE);
+        let moves = MOVE_NAMES.len();
+        assert!(
+            Part::Parameter
+                .parse(&knowing_move, &moves.to_string())
+                .is_ok()
+        );
+        assert!(
+            Part::Parameter
+                .parse(&knowing_move, &(moves + 1).to_string())
+                .is_err()
+        );
+
+        assert_eq!(
+            Part::Parameter.parse(&with_method(0), "1"),
+            Err(String::from("Parameter must be between 0 and 0"))
+        );
+        assert!(Part::Method.parse(&level, "27").is_ok());
+        assert!(Part::Method.parse(&level, "28").is_err());
+        assert!(Part::Target.parse(&level, "650").is_err());
+    }
+}
@@ -1,55 +1,103 @@
 use std::fmt::Display;
 
-use crate::names::move_id_to_name;
+use crate::{
+    names::{POKE_NAMES, move_id_to_name},
+    narc::{ArchiveError, Envelope},
+};
 
 pub const POKEMON_DELIMITER: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
 
 pub struct SaveFile {
-    file_start: Box<[u8]>,
+    envelope: Envelope,
     pub pokemons: Vec<Vec<Move>>,
 }
 
 impl SaveFile {
+    /// Archive holding only the given learnsets, with no file names and an empty first member.
     #[must_use]
-    pub fn to_binary_format(self) -> Vec<u8> {
-        let mut result = Vec::from(self.file_start);
-        result.extend_from_slice(&POKEMON_DELIMITER);
-        for pokemon in self.pokemons {
-            for m in pokemon {
-                let into: [u8; 4] = m.into();
-                result.extend_from_slice(&into);
-            }
-            result.extend_from_slice(&POKEMON_DELIMITER);
+    pub fn from_learnsets(pokemons: Vec<Vec<Move>>) -> Self {
+        Self {
+            envelope: Envelope::unnamed(&POKEMON_DELIMITER),
+            pokemons,
         }
+    }
 
-        result
+    #[must_use]
+    pub fn to_binary_format(&self) -> Vec<u8> {
+        let files: Vec<_> = self
+            .pokemons
+            .iter()
+            .map(|pokemon| learnset_to_binary_format(pokemon))
+            .collect();
+        self.envelope.to_binary_format(&files)
     }
 }
 
 impl TryFrom<&[u8]> for SaveFile {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let (file_start, pokemon) = split(value, &POKEMON_DELIMITER).ok_or(())?;
-        let file_start = Box::from(file_start);
-
-        let pokemons: Vec<Vec<Move>> = {
-            list_pokemon_data(pokemon).map(|pokemon_data| {
-                pokemon_data
-                    .chunks(4)
-                    .map(|chunk| Move::from(&chunk.try_into().unwrap()))
-                    .collect()
-            })
-        }
-        .collect();
+        let (envelope, members) = Envelope::split(value).map_err(ParseError::Archive)?;
+        let pokemons = members
+            .iter()
+            .enumerate()
+            .map(|(pokemon, member)| parse_learnset(pokemon, member.offset, member.data))
+            .collect::<Result<_, _>>()?;
 
-        Ok(Self {
-            file_start,
-            pokemons,
-        })
+        Ok(Self { envelope, pokemons })
+    }
+}
+
+/// Reasons a learnset archive cannot be read, with the absolute byte offset where it went wrong.
+#[derive(Debug)]
+pub enum ParseError {
+    Archive(ArchiveError),
+    MisalignedLearnset {
+        pokemon: usize,
+        offset: usize,
+        len: usize,
+    },
+    MissingDelimiter {
+        pokemon: usize,
+        offset: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::MisalignedLearnset {
+                pokemon,
+                offset,
+                len,
+            } => write!(
+                f,
+                "learnset of {} at offset {offset:#x} is {len} bytes long, \
+                 which is not a multiple of 4 (truncated trailing entry at offset {:#x})",
+                pokemon_name(*pokemon),
+                offset + len - len % 4
+            ),
+            Self::MissingDelimiter { pokemon, offset } => write!(
+                f,
+                "learnset of {} at offset {offset:#x} is not terminated by FF FF FF FF",
+                pokemon_name(*pokemon)
+            ),
+        }
     }
 }
 
+pub fn pokemon_name(index: usize) -> String {
+    POKE_NAMES.get(index).map_or_else(
+        || format!("Pokémon #{}", index + 1),
+        |name| format!("{name} (#{})", index + 1),
+    )
+}
+
+/// Lowest level a move can be learned or an evolution happen at.
+pub const MIN_LEVEL: u16 = 1;
+pub const MAX_LEVEL: u16 = 100;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Move {
     pub id: u16,
@@ -62,9 +110,19 @@ impl Move {
         Self { id, level }
     }
 
+    /// Name of the move, or `-----` for ids outside of [`MOVE_NAMES`](crate::names::MOVE_NAMES).
     #[must_use]
     pub fn name(&self) -> &'static str {
-        move_id_to_name(self.id.into())
+        Self::name_of(self.id)
+    }
+
+    /// Name of the move `id`, as given by [`Move::name`].
+    #[must_use]
+    pub fn name_of(id: u16) -> &'static str {
+        match id {
+            0 => "-----",
+            id => move_id_to_name(id.into()),
+        }
     }
 }
 
@@ -87,32 +145,140 @@ impl From<Move> for [u8; 4] {
 
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "  - {:15} at level {:3}",
-            move_id_to_name(self.id.into()),
-            self.level
-        )
+        write!(f, "  - {:15} at level {:3}", self.name(), self.level)
     }
 }
 
-fn split<'a, T>(haystack: &'a [T], needle: &'_ [T]) -> Option<(&'a [T], &'a [T])>
-where
-    for<'b> &'b [T]: PartialEq,
-{
-    let needle_position = haystack
-        .windows(needle.len())
-        .position(|window| window == needle);
+/// Indices of `moves` in the order the game expects them: by ascending level, moves learned at
+/// the same level keeping their relative order.
+#[must_use]
+pub fn level_order(moves: &[Move]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..moves.len()).collect();
+    order.sort_by_key(|&index| moves[index].level);
+    order
+}
 
-    needle_position.map(|position| haystack.split_at(position))
+fn learnset_to_binary_format(moves: &[Move]) -> Vec<u8> {
+    let mut result = Vec::with_capacity((moves.len() + 1) * 4);
+    for &m in moves {
+        let into: [u8; 4] = m.into();
+        result.extend_from_slice(&into);
+    }
+    result.extend_from_slice(&POKEMON_DELIMITER);
+    result
 }
 
-fn list_pokemon_data(data: &[u8]) -> impl Iterator<Item = &[u8]> {
-    let mut current_remaining_data = &data[POKEMON_DELIMITER.len()..];
-    std::iter::from_fn(move || {
-        split(current_remaining_data, &POKEMON_DELIMITER).map(|(pokemon_data, remaining)| {
-            current_remaining_data = &remaining[POKEMON_DELIMITER.len()..];
-            pokemon_data
-        })
-    })
+/// Parses a single learnset member file: a list of moves terminated by [`POKEMON_DELIMITER`].
+fn parse_learnset(pokemon: usize, offset: usize, data: &[u8]) -> Result<Vec<Move>, ParseError> {
+    if !data.len().is_multiple_of(4) {
+        return Err(ParseError::MisalignedLearnset {
+            pokemon,
+            offset,
+            len: data.len(),
+        });
+    }
+
+    let mut moves = Vec::new();
+    for chunk in data.as_chunks::<4>().0 {
+        if *chunk == POKEMON_DELIMITER {
+            return Ok(moves);
+        }
+        moves.push(Move::from(chunk));
+    }
+    Err(ParseError::MissingDelimiter { pokemon, offset })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::narc::{self, Narc};
+
+    const NAME_TABLE: [u8; 8] = [0x04, 0, 0, 0, 0, 0, 0x01, 0];
+
+    /// Archive whose first member is followed by the given learnset members.
+    fn archive(learnsets: &[&[u8]]) -> Vec<u8> {
+        let mut files = vec![POKEMON_DELIMITER.as_slice()];
+        files.extend_from_slice(learnsets);
+        narc::to_binary_format(&NAME_TABLE, &files)
+    }
+
+    /// Absolute offset of the learnset of `pokemon` in `data`.
+    fn offset_of(data: &[u8], pokemon: usize) -> usize {
+        Narc::try_from(data).unwrap().entries()[pokemon + 1].start
+    }
+
+    #[test]
+    fn learnsets_are_written_back_unchanged() {
+        let data = archive(&[
+            &[0x21, 0, 1, 0, 0x2d, 0, 5, 0, 0xff, 0xff, 0xff, 0xff],
+            &POKEMON_DELIMITER,
+        ]);
+        let save_file = SaveFile::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(
+            save_file.pokemons,
+            [vec![Move::new(33, 1), Move::new(45, 5)], vec![]]
+        );
+        assert_eq!(save_file.to_binary_format(), data);
+    }
+
+    #[test]
+    fn level_order_keeps_moves_of_the_same_level_in_place() {
+        let moves = [
+            Move::new(33, 5),
+            Move::new(45, 1),
+            Move::new(22, 5),
+            Move::new(10, 1),
+        ];
+        assert_eq!(level_order(&moves), [1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn invalid_archives_are_reported() {
+        assert!(matches!(
+            SaveFile::try_from(b"NCGR".as_slice()),
+            Err(ParseError::Archive(ArchiveError::Narc(
+                narc::Error::BadMagic { offset: 0, .. }
+            )))
+        ));
+    }
+
+    #[test]
+    fn archives_without_files_are_reported() {
+        let data = narc::to_binary_format::<&[u8]>(&NAME_TABLE, &[]);
+        assert!(matches!(
+            SaveFile::try_from(data.as_slice()),
+            Err(ParseError::Archive(ArchiveError::Empty))
+        ));
+    }
+
+    #[test]
+    fn misaligned_learnsets_are_reported_at_their_offset() {
+        let data = archive(&[&POKEMON_DELIMITER, &[0x21, 0, 1, 0, 0xff, 0xff]]);
+        let error = SaveFile::try_from(data.as_slice()).err().unwrap();
+
+        assert!(matches!(
+            error,
+            ParseError::MisalignedLearnset {
+                pokemon: 1,
+                offset,
+                len: 6,
+            } if offset == offset_of(&data, 1)
+        ));
+        assert!(
+            error
+                .to_string()
+                .contains(&format!("offset {:#x})", offset_of(&data, 1) + 4))
+        );
+    }
+
+    #[test]
+    fn unterminated_learnsets_are_reported_at_their_offset() {
+        let data = archive(&[&POKEMON_DELIMITER, &[0x21, 0, 1, 0]]);
+        assert!(matches!(
+            SaveFile::try_from(data.as_slice()),
+            Err(ParseError::MissingDelimiter { pokemon: 1, offset })
+                if offset == offset_of(&data, 1)
+        ));
+    }
 }
@@ -0,0 +1,18 @@
+//! Values typed by the user for the numeric fields of the archives.
+
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+/// Parses `input` as a value of `range`, naming the field in the error otherwise.
+pub fn parse_in_range<T>(name: &str, range: &RangeInclusive<T>, input: &str) -> Result<T, String>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    match input.parse() {
+        Ok(value) if range.contains(&value) => Ok(value),
+        _ => Err(format!(
+            "{name} must be between {} and {}",
+            range.start(),
+            range.end()
+        )),
+    }
+}
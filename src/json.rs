@@ -0,0 +1,348 @@
+//! JSON representation of the learnsets, meant to be kept under version control and reviewed.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+
+use crate::{
+    file::{self, MAX_LEVEL, MIN_LEVEL, SaveFile},
+    names::{MOVE_NAMES, POKE_NAMES, move_id_to_name, move_name_to_id, pokemon_name_to_index},
+};
+
+#[derive(Serialize)]
+struct Document<'a> {
+    pokemons: Vec<Pokemon<'a>>,
+}
+
+#[derive(Serialize)]
+struct Pokemon<'a> {
+    /// National index, starting at 1 like in the editor.
+    index: usize,
+    /// Left out for members past the known Pokémon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    moves: Vec<Move<'a>>,
+}
+
+#[derive(Serialize)]
+struct Move<'a> {
+    id: u16,
+    name: &'a str,
+    level: u16,
+}
+
+/// Renders every learnset of `save_file` as a pretty-printed JSON document.
+#[must_use]
+pub fn export(save_file: &SaveFile) -> String {
+    let pokemons = save_file
+        .pokemons
+        .iter()
+        .enumerate()
+        .map(|(index, moves)| Pokemon {
+            index: index + 1,
+            name: POKE_NAMES.get(index).copied(),
+            moves: moves
+                .iter()
+                .map(|m| Move {
+                    id: m.id,
+                    name: m.name(),
+                    level: m.level,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let mut result = serde_json::to_string_pretty(&Document { pokemons })
+        .expect("learnsets are always serializable");
+    result.push('\n');
+    result
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedDocument {
+    pokemons: Vec<ImportedPokemon>,
+}
+
+/// A Pokémon given by its index, its name, or both as long as they agree.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedPokemon {
+    #[serde(default, deserialize_with = "pokemon_index")]
+    index: Option<usize>,
+    #[serde(default, deserialize_with = "pokemon_name")]
+    name: Option<usize>,
+    moves: Vec<ImportedMove>,
+}
+
+/// A move given by its id, its name, or both as long as they agree.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportedMove {
+    #[serde(default, deserialize_with = "move_id")]
+    id: Option<u16>,
+    #[serde(default, deserialize_with = "move_name")]
+    name: Option<u16>,
+    #[serde(deserialize_with = "level")]
+    level: u16,
+}
+
+/// Reads a JSON document as produced by [`export`] into learnsets.
+///
+/// Learnsets of the Pokémon missing from the document are taken from `base` when given, and left
+/// empty otherwise. Values that cannot be resolved are reported with their line and column, and
+/// inconsistencies between entries with the index of the entry.
+pub fn import(document: &str, base: Option<SaveFile>) -> Result<SaveFile, ImportError> {
+    let document: ImportedDocument = serde_json::from_str(document).map_err(ImportError::Json)?;
+    let mut save_file =
+        base.unwrap_or_else(|| SaveFile::from_learnsets(vec![Vec::new(); POKE_NAMES.len()]));
+    let mut seen = vec![false; save_file.pokemons.len()];
+
+    for (entry, pokemon) in document.pokemons.into_iter().enumerate() {
+        let index = match (pokemon.index, pokemon.name) {
+            (Some(index), Some(name)) if index != name => {
+                return Err(ImportError::PokemonMismatch { entry, index, name });
+            }
+            (Some(index), _) | (None, Some(index)) => index,
+            (None, None) => return Err(ImportError::MissingPokemon { entry }),
+        };
+        let Some(already_seen) = seen.get_mut(index) else {
+            return Err(ImportError::PokemonOutOfArchive { entry, index });
+        };
+        if std::mem::replace(already_seen, true) {
+            return Err(ImportError::DuplicatePokemon { entry, index });
+        }
+
+        save_file.pokemons[index] = pokemon
+            .moves
+            .into_iter()
+            .enumerate()
+            .map(|(move_entry, imported)| {
+                let id = match (imported.id, imported.name) {
+                    (Some(id), Some(name)) if id != name => {
+                        return Err(ImportError::MoveMismatch {
+                            entry,
+                            index,
+                            move_entry,
+                            id,
+                            name,
+                        });
+                    }
+                    (Some(id), _) | (None, Some(id)) => id,
+                    (None, None) => {
+                        return Err(ImportError::MissingMove {
+                            entry,
+                            index,
+                            move_entry,
+                        });
+                    }
+                };
+                Ok(file::Move::new(id, imported.level))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    Ok(save_file)
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// Malformed document or unresolvable value, with its line and column.
+    Json(serde_json::Error),
+    MissingPokemon {
+        entry: usize,
+    },
+    PokemonMismatch {
+        entry: usize,
+        index: usize,
+        name: usize,
+    },
+    PokemonOutOfArchive {
+        entry: usize,
+        index: usize,
+    },
+    DuplicatePokemon {
+        entry: usize,
+        index: usize,
+    },
+    MissingMove {
+        entry: usize,
+        index: usize,
+        move_entry: usize,
+    },
+    MoveMismatch {
+        entry: usize,
+        index: usize,
+        move_entry: usize,
+        id: u16,
+        name: u16,
+    },
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Json(ref error) => write!(f, "{error}"),
+            Self::MissingPokemon { entry } => {
+                write!(
+                    f,
+                    "pokemons[{entry}]: either an index or a name is required"
+                )
+            }
+            Self::PokemonMismatch { entry, index, name } => write!(
+                f,
+                "pokemons[{entry}]: index {} is not {}",
+                index + 1,
+                file::pokemon_name(name)
+            ),
+            Self::PokemonOutOfArchive { entry, index } => write!(
+                f,
+                "pokemons[{entry}], {}: the base archive has no learnset for it",
+                file::pokemon_name(index)
+            ),
+            Self::DuplicatePokemon { entry, index } => write!(
+                f,
+                "pokemons[{entry}], {}: the Pokémon is listed more than once",
+                file::pokemon_name(index)
+            ),
+            Self::MissingMove {
+                entry,
+                index,
+                move_entry,
+            } => write!(
+                f,
+                "pokemons[{entry}], {}, moves[{move_entry}]: either an id or a name is required",
+                file::pokemon_name(index)
+            ),
+            Self::MoveMismatch {
+                entry,
+                index,
+                move_entry,
+                id,
+                name,
+            } => write!(
+                f,
+                "pokemons[{entry}], {}, moves[{move_entry}]: id {id} is {}, not {}",
+                file::pokemon_name(index),
+                move_id_to_name(id.into()),
+                move_id_to_name(name.into())
+            ),
+        }
+    }
+}
+
+fn pokemon_index<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    // Whether the archive has a learnset for it is only known once the base archive is read.
+    match usize::deserialize(deserializer)? {
+        0 => Err(D::Error::custom("Pokémon indices start at 1")),
+        index => Ok(Some(index - 1)),
+    }
+}
+
+fn pokemon_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    pokemon_name_to_index(&name)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("unknown Pokémon {name:?}")))
+}
+
+fn move_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u16>, D::Error> {
+    let id = u16::deserialize(deserializer)?;
+    if (1..=MOVE_NAMES.len()).contains(&usize::from(id)) {
+        Ok(Some(id))
+    } else {
+        Err(D::Error::custom(format!(
+            "move id {id} is not between 1 and {}",
+            MOVE_NAMES.len()
+        )))
+    }
+}
+
+fn move_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u16>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    move_name_to_id(&name)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("unknown move {name:?}")))
+}
+
+fn level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    let level = u16::deserialize(deserializer)?;
+    if (MIN_LEVEL..=MAX_LEVEL).contains(&level) {
+        Ok(level)
+    } else {
+        Err(D::Error::custom(format!(
+            "level {level} is not between {MIN_LEVEL} and {MAX_LEVEL}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_file(count: usize) -> SaveFile {
+        SaveFile::from_learnsets(
+            (0..count)
+                .map(|pokemon| {
+                    let id = u16::try_from(pokemon % MOVE_NAMES.len()).unwrap() + 1;
+                    vec![file::Move::new(id, 1), file::Move::new(33, 100)]
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn exported_learnsets_are_imported_back() {
+        let original = save_file(POKE_NAMES.len());
+        let imported = import(&export(&original), None).unwrap();
+        assert_eq!(imported.pokemons, original.pokemons);
+    }
+
+    #[test]
+    fn members_past_the_known_pokemon_round_trip_without_a_name() {
+        let original = save_file(POKE_NAMES.len() + 41);
+        let document = export(&original);
+        assert!(!document.contains("\"name\": \"\""));
+
+        let base = SaveFile::from_learnsets(vec![Vec::new(); original.pokemons.len()]);
+        let imported = import(&document, Some(base)).unwrap();
+        assert_eq!(imported.pokemons, original.pokemons);
+    }
+
+    #[test]
+    fn pokemons_missing_from_the_document_are_taken_from_the_base() {
+        let base = save_file(POKE_NAMES.len());
+        let document = r#"{"pokemons": [
+            {"name": "ivysaur", "moves": [{"name": "Tackle", "level": 5}]},
+            {"index": 3, "name": "Venusaur", "moves": [{"id": 33, "name": "Tackle", "level": 7}]}
+        ]}"#;
+        let imported = import(document, Some(save_file(POKE_NAMES.len()))).unwrap();
+
+        assert_eq!(imported.pokemons[0], base.pokemons[0]);
+        assert_eq!(imported.pokemons[1], [file::Move::new(33, 5)]);
+        assert_eq!(imported.pokemons[2], [file::Move::new(33, 7)]);
+        assert_eq!(imported.pokemons[3..], base.pokemons[3..]);
+    }
+
+    #[test]
+    fn members_past_the_base_archive_are_rejected() {
+        let document = r#"{"pokemons": [{"index": 700, "moves": []}]}"#;
+        assert!(matches!(
+            import(document, None),
+            Err(ImportError::PokemonOutOfArchive { index: 699, .. })
+        ));
+    }
+
+    #[test]
+    fn index_and_name_must_agree() {
+        let document = r#"{"pokemons": [{"index": 2, "name": "Bulbasaur", "moves": []}]}"#;
+        assert!(matches!(
+            import(document, None),
+            Err(ImportError::PokemonMismatch {
+                index: 1,
+                name: 0,
+                ..
+            })
+        ));
+    }
+}
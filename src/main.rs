@@ -1,23 +1,224 @@
-use file::SaveFile;
-use std::fs;
+use std::{io::Write, path::Path, process::ExitCode};
 
+use clap::Parser;
+use cli::{Cli, Command, PatchFormat};
+
+mod archives;
+mod bytes;
+mod check;
+mod cli;
+mod diff;
+mod egg_moves;
+mod evolution;
 mod file;
+mod input;
+mod json;
+mod move_data;
 mod names;
+mod narc;
+mod nds;
+mod patch;
+mod personal;
 mod tui;
 
-fn main() {
-    let result = tui::run(ratatui::init(), {
-        let bytes = fs::read("./a018.narc").expect("Should have been able to read the file");
-        SaveFile::try_from(bytes.as_ref()).expect("Parsing to work")
-    });
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), cli::Error> {
+    match (cli.command, cli.edit) {
+        (
+            Some(Command::Export {
+                input,
+                output,
+                force,
+            }),
+            _,
+        ) => export(&input, output.as_deref(), force),
+        (
+            Some(Command::Import {
+                input,
+                output,
+                base,
+                force,
+            }),
+            _,
+        ) => import(
+            &input,
+            output.as_deref().unwrap_or(Path::new(cli::DEFAULT_OUTPUT)),
+            base.as_deref(),
+            force,
+        ),
+        (
+            Some(Command::Patch {
+                original,
+                modified,
+                output,
+                format,
+                force,
+            }),
+            _,
+        ) => create_patches(
+            &original,
+            &modified,
+            output.as_deref().unwrap_or(&modified),
+            format,
+            force,
+        ),
+        (
+            Some(Command::Apply {
+                patch,
+                input,
+                output,
+                force,
+            }),
+            _,
+        ) => apply_patch(&patch, &input, output.as_deref(), force),
+        (Some(Command::Diff { old, new, json }), _) => diff(&old, &new, json),
+        (Some(Command::Check { input, strict }), _) => check(&input, strict),
+        (None, Some(edit)) => run_editor(&edit),
+        (None, None) => unreachable!("clap requires an input file when no command is given"),
+    }
+}
+
+fn run_editor(edit: &cli::Edit) -> Result<(), cli::Error> {
+    edit.check_output()?;
+    let (container, save_file) = cli::Container::open(&edit.input)?;
+    let archives = container.open_archives(&edit.archive_paths())?;
+    let reference = match &edit.compare {
+        Some(path) => Some((path.display().to_string(), cli::load(path)?)),
+        None => None,
+    };
+    let options = tui::Options {
+        reference,
+        auto_sort: edit.auto_sort,
+    };
+
+    let mut saved_once = false;
+    tui::run(
+        ratatui::init(),
+        save_file,
+        archives,
+        options,
+        |save_file, archives| {
+            edit.write_output(&container, save_file, archives, saved_once)?;
+            saved_once = true;
+            Ok::<_, cli::Error>(())
+        },
+    );
     ratatui::restore();
-    save_to_file(result)
+    Ok(())
+}
+
+fn export(input: &Path, output: Option<&Path>, force: bool) -> Result<(), cli::Error> {
+    let document = json::export(&cli::load(input)?);
+    match output {
+        Some(output) => cli::write(output, document.as_bytes(), force),
+        None => std::io::stdout()
+            .write_all(document.as_bytes())
+            .map_err(|source| cli::Error::Write("standard output".into(), source)),
+    }
+}
+
+fn import(input: &Path, output: &Path, base: Option<&Path>, force: bool) -> Result<(), cli::Error> {
+    let document = std::fs::read_to_string(input)
+        .map_err(|source| cli::Error::Read(input.to_path_buf(), source))?;
+    let base = base.map(cli::load).transpose()?;
+    let save_file = json::import(&document, base)
+        .map_err(|error| cli::Error::Import(input.to_path_buf(), error))?;
+    cli::write(output, &save_file.to_binary_format(), force)
+}
+
+fn create_patches(
+    original: &Path,
+    modified: &Path,
+    output: &Path,
+    format: PatchFormat,
+    force: bool,
+) -> Result<(), cli::Error> {
+    let source = cli::read(original)?;
+    let target = cli::read(modified)?;
+
+    if format != PatchFormat::Bps {
+        match patch::ips::create(&source, &target) {
+            Ok(ips) => cli::write(&output.with_extension("ips"), &ips, force)?,
+            Err(error) if format == PatchFormat::Both => {
+                eprintln!("warning: skipping IPS: {error}")
+            }
+            Err(error) => return Err(cli::Error::Patch(output.with_extension("ips"), error)),
+        }
+    }
+    if format != PatchFormat::Ips {
+        let bps = patch::bps::create(&source, &target);
+        cli::write(&output.with_extension("bps"), &bps, force)?;
+    }
+    Ok(())
+}
+
+fn apply_patch(
+    patch: &Path,
+    input: &Path,
+    output: Option<&Path>,
+    force: bool,
+) -> Result<(), cli::Error> {
+    let patched = patch::apply(&cli::read(patch)?, &cli::read(input)?)
+        .map_err(|error| cli::Error::Patch(patch.to_path_buf(), error))?;
+    let len = patched.len();
+    if let Some(output) = output {
+        cli::write(output, &patched, force)?;
+    }
+    let (_, save_file) = cli::Container::from_bytes(output.unwrap_or(input), patched)?;
+    println!(
+        "{} applies cleanly: {len} bytes, {} learnsets",
+        patch.display(),
+        save_file.pokemons.len()
+    );
+    Ok(())
+}
+
+fn diff(old: &Path, new: &Path, json: bool) -> Result<(), cli::Error> {
+    let pokemons = diff::save_files(&cli::load(old)?, &cli::load(new)?);
+    let mut output = String::new();
+    if json {
+        output = serde_json::to_string_pretty(&pokemons).expect("diffs are always serializable");
+        output.push('\n');
+    } else {
+        for pokemon in pokemons {
+            output += &format!("{}\n", file::pokemon_name(pokemon.index - 1));
+            for change in pokemon.changes {
+                output += &format!("  {change}\n");
+            }
+        }
+    }
+    std::io::stdout()
+        .write_all(output.as_bytes())
+        .map_err(|source| cli::Error::Write("standard output".into(), source))
 }
 
-fn save_to_file(save_file: SaveFile) {
-    std::io::Write::write_all(
-        &mut fs::File::create_new("out.narc").unwrap(),
-        &save_file.to_binary_format(),
-    )
-    .expect("Writing to file to work");
+fn check(input: &Path, strict: bool) -> Result<(), cli::Error> {
+    let problems = check::check(&cli::load(input)?);
+    for problem in &problems {
+        println!("{}: {problem}", problem.severity());
+    }
+    let summary = check::Summary::of(&problems);
+    if summary.fails(strict) {
+        return Err(cli::Error::Check {
+            path: input.to_path_buf(),
+            errors: summary.errors,
+            warnings: summary.warnings,
+        });
+    }
+    println!(
+        "{}: {} errors, {} warnings",
+        input.display(),
+        summary.errors,
+        summary.warnings
+    );
+    Ok(())
 }
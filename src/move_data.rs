@@ -0,0 +1,410 @@
+//! Move data archive (`a/0/2/1`): type, category, power, accuracy and effects of each move,
+//! indexed by move id from 0.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{
+    input::parse_in_range,
+    names::{TYPE_NAMES, move_id_to_name},
+    narc,
+};
+
+const ENTRY_LEN: usize = 0x24;
+const FLAGS_OFFSET: usize = 0x20;
+/// Accuracy of the moves that cannot miss.
+const NEVER_MISSES: i32 = 101;
+
+/// Damage categories, indexed by their id.
+pub const CATEGORY_NAMES: [&str; 3] = ["Status", "Physical", "Special"];
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct MoveDataFile {
+    name_table: Box<[u8]>,
+    /// Data of each move, the first one being the empty move 0.
+    pub moves: Vec<MoveData>,
+}
+
+impl MoveDataFile {
+    #[must_use]
+    pub fn to_binary_format(&self) -> Vec<u8> {
+        let files: Vec<&[u8]> = self.moves.iter().map(|data| &*data.0).collect();
+        narc::to_binary_format(&self.name_table, &files)
+    }
+
+    /// Data of the move `id`, if the archive has it.
+    #[must_use]
+    pub fn get(&self, id: u16) -> Option<&MoveData> {
+        self.moves.get(usize::from(id))
+    }
+}
+
+impl TryFrom<&[u8]> for MoveDataFile {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (name_table, members) = narc::members(value).map_err(ParseError::Archive)?;
+        let moves = members
+            .iter()
+            .enumerate()
+            .map(|(id, member)| {
+                if member.data.len() < ENTRY_LEN {
+                    return Err(ParseError::ShortEntry {
+                        id,
+                        offset: member.offset,
+                        len: member.data.len(),
+                    });
+                }
+                Ok(MoveData(Box::from(member.data)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name_table: Box::from(name_table),
+            moves,
+        })
+    }
+}
+
+/// Reasons a move data archive cannot be read.
+#[derive(Debug)]
+pub enum ParseError {
+    Archive(narc::ArchiveError),
+    ShortEntry {
+        id: usize,
+        offset: usize,
+        len: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::ShortEntry { id, offset, len } => write!(
+                f,
+                "data of {} at offset {offset:#x} is {len} bytes long, {ENTRY_LEN} were expected",
+                move_name(*id)
+            ),
+        }
+    }
+}
+
+fn move_name(id: usize) -> String {
+    match id {
+        0 => String::from("move #0"),
+        id => format!("{} (#{id})", move_id_to_name(id)),
+    }
+}
+
+/// Data of one move, kept as read so bytes no field covers are written back as is.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MoveData(Box<[u8]>);
+
+impl MoveData {
+    #[must_use]
+    pub fn get(&self, field: Field) -> i32 {
+        let offset = field.offset();
+        match field {
+            Field::Priority => i32::from(self.0[offset].cast_signed()),
+            Field::Effect => i32::from(u16::from_le_bytes([self.0[offset], self.0[offset + 1]])),
+            _ => i32::from(self.0[offset]),
+        }
+    }
+
+    /// Sets a field to a value within [`Field::range`].
+    pub fn set(&mut self, field: Field, value: i32) {
+        debug_assert!(field.range().contains(&value));
+        let offset = field.offset();
+        match field {
+            Field::Effect => {
+                let value = u16::try_from(value).unwrap_or_default();
+                self.0[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            _ => self.0[offset] = value.to_le_bytes()[0],
+        }
+    }
+
+    #[must_use]
+    pub fn has_flag(&self, flag: Flag) -> bool {
+        let (offset, bit) = flag.bit();
+        self.0[offset] & bit != 0
+    }
+
+    /// Sets a single flag, leaving the other bits untouched.
+    pub fn set_flag(&mut self, flag: Flag, set: bool) {
+        let (offset, bit) = flag.bit();
+        if set {
+            self.0[offset] |= bit;
+        } else {
+            self.0[offset] &= !bit;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Type,
+    Category,
+    Power,
+    Accuracy,
+    Pp,
+    Priority,
+    /// Battle effect sequence run when the move is used.
+    Effect,
+}
+
+impl Field {
+    pub const ALL: [Self; 7] = [
+        Self::Type,
+        Self::Category,
+        Self::Power,
+        Self::Accuracy,
+        Self::Pp,
+        Self::Priority,
+        Self::Effect,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Type => "Type",
+            Self::Category => "Category",
+            Self::Power => "Power",
+            Self::Accuracy => "Accuracy",
+            Self::Pp => "PP",
+            Self::Priority => "Priority",
+            Self::Effect => "Effect",
+        }
+    }
+
+    /// Name in the header of the moves table.
+    #[must_use]
+    pub const fn short_name(self) -> &'static str {
+        match self {
+            Self::Type => "Type",
+            Self::Category => "Cat.",
+            Self::Power => "Pow.",
+            Self::Accuracy => "Acc.",
+            Self::Pp => "PP",
+            Self::Priority => "Prio.",
+            Self::Effect => "Eff.",
+        }
+    }
+
+    /// Byte offset of the field in an entry.
+    const fn offset(self) -> usize {
+        match self {
+            Self::Type => 0x00,
+            Self::Category => 0x02,
+            Self::Power => 0x03,
+            Self::Accuracy => 0x04,
+            Self::Pp => 0x05,
+            Self::Priority => 0x06,
+            Self::Effect => 0x10,
+        }
+    }
+
+    /// Values the games handle for this field.
+    #[must_use]
+    pub const fn range(self) -> RangeInclusive<i32> {
+        match self {
+            Self::Type => 0..=TYPE_NAMES.len() as i32 - 1,
+            Self::Category => 0..=CATEGORY_NAMES.len() as i32 - 1,
+            Self::Power | Self::Pp => 0..=255,
+            Self::Accuracy => 0..=NEVER_MISSES,
+            Self::Priority => -7..=7,
+            Self::Effect => 0..=u16::MAX as i32,
+        }
+    }
+
+    /// Parses a value typed by the user, checking it against [`Field::range`].
+    pub fn parse(self, input: &str) -> Result<i32, String> {
+        parse_in_range(self.name(), &self.range(), input)
+    }
+
+    /// What a value of this field means, for the fields holding an id or a special value.
+    #[must_use]
+    pub fn describe(self, value: i32) -> String {
+        let name = |names: &[&str]| {
+            usize::try_from(value)
+                .ok()
+                .and_then(|index| names.get(index))
+                .map_or_else(|| String::from("?"), ToString::to_string)
+        };
+        match self {
+            Self::Type => name(&TYPE_NAMES),
+            Self::Category => name(&CATEGORY_NAMES),
+            Self::Power if value == 0 => String::from("No damage or variable"),
+            Self::Accuracy if value == NEVER_MISSES => String::from("Never misses"),
+            _ => String::new(),
+        }
+    }
+
+    /// Value in the columns of the moves table, where special values are shown as `-`.
+    #[must_use]
+    pub fn short(self, value: i32) -> String {
+        match self {
+            Self::Type => self.describe(value),
+            Self::Category => match value {
+                0 => String::from("Status"),
+                1 => String::from("Phys."),
+                2 => String::from("Spec."),
+                _ => String::from("?"),
+            },
+            Self::Power if value == 0 => String::from("-"),
+            Self::Accuracy if value == NEVER_MISSES => String::from("-"),
+            Self::Priority if value > 0 => format!("+{value}"),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Properties of a move stored as single bits, such as whether it makes contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    Contact,
+    Charge,
+    Recharge,
+    Protect,
+    MagicCoat,
+    Snatch,
+    MirrorMove,
+    Punch,
+    Sound,
+    Gravity,
+    Defrost,
+    NonAdjacent,
+    Heal,
+    BypassSubstitute,
+}
+
+impl Flag {
+    pub const ALL: [Self; 14] = [
+        Self::Contact,
+        Self::Charge,
+        Self::Recharge,
+        Self::Protect,
+        Self::MagicCoat,
+        Self::Snatch,
+        Self::MirrorMove,
+        Self::Punch,
+        Self::Sound,
+        Self::Gravity,
+        Self::Defrost,
+        Self::NonAdjacent,
+        Self::Heal,
+        Self::BypassSubstitute,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Contact => "Makes contact",
+            Self::Charge => "Charges for a turn",
+            Self::Recharge => "Recharges for a turn",
+            Self::Protect => "Blocked by Protect",
+            Self::MagicCoat => "Reflected by Magic Coat",
+            Self::Snatch => "Stolen by Snatch",
+            Self::MirrorMove => "Copied by Mirror Move",
+            Self::Punch => "Punching move",
+            Self::Sound => "Sound-based",
+            Self::Gravity => "Disabled by Gravity",
+            Self::Defrost => "Thaws the user",
+            Self::NonAdjacent => "Reaches any target in triples",
+            Self::Heal => "Healing move",
+            Self::BypassSubstitute => "Bypasses Substitute",
+        }
+    }
+
+    /// Byte offset of the flag in an entry, and its mask in that byte.
+    const fn bit(self) -> (usize, u8) {
+        let bit = self as usize;
+        (FLAGS_OFFSET + bit / 8, 1 << (bit % 8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::narc::Envelope;
+
+    /// Entry of Tackle: Normal, physical, 50 power, 100% accuracy and 35 PP, making contact and
+    /// blocked by Protect. The bytes no field covers hold a pattern.
+    fn tackle(len: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..len)
+            .map(|index| (index as u8).wrapping_mul(37))
+            .collect();
+        data[..0x07].copy_from_slice(&[0, 0x25, 1, 50, 100, 35, 0]);
+        data[0x10..0x12].copy_from_slice(&[0, 0]);
+        data[FLAGS_OFFSET..FLAGS_OFFSET + 4].copy_from_slice(&[0b0100_1001, 0, 0, 0]);
+        data
+    }
+
+    /// Archive holding the empty move 0 followed by `moves`.
+    fn archive(moves: &[Vec<u8>]) -> Vec<u8> {
+        Envelope::unnamed(&[0; ENTRY_LEN]).to_binary_format(moves)
+    }
+
+    #[test]
+    fn fields_and_flags_are_read_at_their_offset() {
+        let data = archive(&[tackle(ENTRY_LEN)]);
+        let moves = MoveDataFile::try_from(data.as_slice()).unwrap();
+        let tackle = moves.get(1).unwrap();
+
+        assert_eq!(tackle.get(Field::Category), 1);
+        assert_eq!(tackle.get(Field::Power), 50);
+        assert_eq!(tackle.get(Field::Accuracy), 100);
+        assert_eq!(tackle.get(Field::Pp), 35);
+        assert!(tackle.has_flag(Flag::Contact));
+        assert!(tackle.has_flag(Flag::Protect));
+        assert!(!tackle.has_flag(Flag::Charge));
+        assert!(moves.get(2).is_none());
+    }
+
+    #[test]
+    fn entries_are_reencoded_with_their_unknown_bytes() {
+        let data = archive(&[tackle(ENTRY_LEN), tackle(ENTRY_LEN + 2)]);
+        let mut moves = MoveDataFile::try_from(data.as_slice()).unwrap();
+        for data in &mut moves.moves {
+            for field in Field::ALL {
+                data.set(field, data.get(field));
+            }
+            for flag in Flag::ALL {
+                data.set_flag(flag, data.has_flag(flag));
+            }
+        }
+
+        assert_eq!(moves.to_binary_format(), data);
+    }
+
+    #[test]
+    fn setting_values_keeps_the_bytes_around_them() {
+        let mut data = MoveData(Box::from(tackle(ENTRY_LEN).as_slice()));
+        let expected = {
+            let mut expected = tackle(ENTRY_LEN);
+            expected[0x06] = 0xfa;
+            expected[0x10..0x12].copy_from_slice(&0x0123_u16.to_le_bytes());
+            expected[FLAGS_OFFSET + 1] = 0x01;
+            expected
+        };
+        data.set(Field::Priority, -6);
+        data.set(Field::Effect, 0x0123);
+        data.set_flag(Flag::Sound, true);
+
+        assert_eq!(data.get(Field::Priority), -6);
+        assert_eq!(*data.0, *expected);
+    }
+
+    #[test]
+    fn short_entries_are_reported_at_their_offset() {
+        let mut short = tackle(ENTRY_LEN);
+        short.pop();
+        let data = archive(&[short]);
+        let (_, members) = narc::members(&data).unwrap();
+
+        assert!(matches!(
+            MoveDataFile::try_from(data.as_slice()),
+            Err(ParseError::ShortEntry { id: 1, offset, len: 0x23 }) if offset == members[1].offset
+        ));
+    }
+}
@@ -1230,8 +1230,258 @@ pub const MOVE_NAMES: [&str; 559] = [
     "Fusion Bolt",
 ];
 
+/// Types, indexed by their id.
+pub const TYPE_NAMES: [&str; 17] = [
+    "Normal", "Fighting", "Flying", "Poison", "Ground", "Rock", "Bug", "Ghost", "Steel", "Fire",
+    "Water", "Grass", "Electric", "Psychic", "Ice", "Dragon", "Dark",
+];
+
+/// Abilities, the first one having id 1, as id 0 means no ability.
+pub const ABILITY_NAMES: [&str; 164] = [
+    "Stench",
+    "Drizzle",
+    "Speed Boost",
+    "Battle Armor",
+    "Sturdy",
+    "Damp",
+    "Limber",
+    "Sand Veil",
+    "Static",
+    "Volt Absorb",
+    "Water Absorb",
+    "Oblivious",
+    "Cloud Nine",
+    "Compound Eyes",
+    "Insomnia",
+    "Color Change",
+    "Immunity",
+    "Flash Fire",
+    "Shield Dust",
+    "Own Tempo",
+    "Suction Cups",
+    "Intimidate",
+    "Shadow Tag",
+    "Rough Skin",
+    "Wonder Guard",
+    "Levitate",
+    "Effect Spore",
+    "Synchronize",
+    "Clear Body",
+    "Natural Cure",
+    "Lightning Rod",
+    "Serene Grace",
+    "Swift Swim",
+    "Chlorophyll",
+    "Illuminate",
+    "Trace",
+    "Huge Power",
+    "Poison Point",
+    "Inner Focus",
+    "Magma Armor",
+    "Water Veil",
+    "Magnet Pull",
+    "Soundproof",
+    "Rain Dish",
+    "Sand Stream",
+    "Pressure",
+    "Thick Fat",
+    "Early Bird",
+    "Flame Body",
+    "Run Away",
+    "Keen Eye",
+    "Hyper Cutter",
+    "Pickup",
+    "Truant",
+    "Hustle",
+    "Cute Charm",
+    "Plus",
+    "Minus",
+    "Forecast",
+    "Sticky Hold",
+    "Shed Skin",
+    "Guts",
+    "Marvel Scale",
+    "Liquid Ooze",
+    "Overgrow",
+    "Blaze",
+    "Torrent",
+    "Swarm",
+    "Rock Head",
+    "Drought",
+    "Arena Trap",
+    "Vital Spirit",
+    "White Smoke",
+    "Pure Power",
+    "Shell Armor",
+    "Air Lock",
+    "Tangled Feet",
+    "Motor Drive",
+    "Rivalry",
+    "Steadfast",
+    "Snow Cloak",
+    "Gluttony",
+    "Anger Point",
+    "Unburden",
+    "Heatproof",
+    "Simple",
+    "Dry Skin",
+    "Download",
+    "Iron Fist",
+    "Poison Heal",
+    "Adaptability",
+    "Skill Link",
+    "Hydration",
+    "Solar Power",
+    "Quick Feet",
+    "Normalize",
+    "Sniper",
+    "Magic Guard",
+    "No Guard",
+    "Stall",
+    "Technician",
+    "Leaf Guard",
+    "Klutz",
+    "Mold Breaker",
+    "Super Luck",
+    "Aftermath",
+    "Anticipation",
+    "Forewarn",
+    "Unaware",
+    "Tinted Lens",
+    "Filter",
+    "Slow Start",
+    "Scrappy",
+    "Storm Drain",
+    "Ice Body",
+    "Solid Rock",
+    "Snow Warning",
+    "Honey Gather",
+    "Frisk",
+    "Reckless",
+    "Multitype",
+    "Flower Gift",
+    "Bad Dreams",
+    "Pickpocket",
+    "Sheer Force",
+    "Contrary",
+    "Unnerve",
+    "Defiant",
+    "Defeatist",
+    "Cursed Body",
+    "Healer",
+    "Friend Guard",
+    "Weak Armor",
+    "Heavy Metal",
+    "Light Metal",
+    "Multiscale",
+    "Toxic Boost",
+    "Flare Boost",
+    "Harvest",
+    "Telepathy",
+    "Moody",
+    "Overcoat",
+    "Poison Touch",
+    "Regenerator",
+    "Big Pecks",
+    "Sand Rush",
+    "Wonder Skin",
+    "Analytic",
+    "Illusion",
+    "Imposter",
+    "Infiltrator",
+    "Mummy",
+    "Moxie",
+    "Justified",
+    "Rattled",
+    "Magic Bounce",
+    "Sap Sipper",
+    "Prankster",
+    "Sand Force",
+    "Iron Barbs",
+    "Zen Mode",
+    "Victory Star",
+    "Turboblaze",
+    "Teravolt",
+];
+
+/// Egg groups, the first one having id 1.
+pub const EGG_GROUP_NAMES: [&str; 15] = [
+    "Monster",
+    "Water 1",
+    "Bug",
+    "Flying",
+    "Field",
+    "Fairy",
+    "Grass",
+    "Human-Like",
+    "Water 3",
+    "Mineral",
+    "Amorphous",
+    "Water 2",
+    "Ditto",
+    "Dragon",
+    "Undiscovered",
+];
+
+/// Experience growth rates, indexed by their id.
+pub const GROWTH_RATE_NAMES: [&str; 6] = [
+    "Medium Fast",
+    "Erratic",
+    "Fluctuating",
+    "Medium Slow",
+    "Fast",
+    "Slow",
+];
+
+/// Move ids taught by TM01 to TM95, then by HM01 to HM06.
+pub const MACHINE_MOVES: [u16; 101] = [
+    468, 337, 473, 347, 46, 92, 258, 339, 474, 237, 241, 269, 58, 59, 63, 113, 182, 240, 477, 219,
+    218, 76, 479, 85, 87, 89, 216, 91, 94, 247, 280, 104, 115, 482, 53, 188, 201, 126, 317, 332,
+    259, 263, 488, 156, 213, 168, 490, 496, 497, 315, 502, 411, 412, 206, 503, 374, 451, 507, 510,
+    511, 261, 512, 373, 153, 421, 371, 514, 416, 397, 148, 444, 521, 86, 360, 14, 522, 244, 523,
+    524, 157, 404, 525, 526, 398, 138, 447, 207, 365, 369, 164, 430, 433, 528, 249, 555, 15, 19,
+    57, 70, 127, 291,
+];
+
+/// Move ids taught by the tutors of starter Pokémon and Dragon types: the Pledge moves, the
+/// ultimate starter moves and Draco Meteor.
+pub const SPECIAL_TUTOR_MOVES: [u16; 7] = [520, 519, 518, 338, 307, 308, 434];
+
+/// Move ids taught by the Black 2 and White 2 tutors of Driftveil City, Lentimas Town, Humilau
+/// City and Nacrene City, in the order of their compatibility bits.
+pub const TUTOR_MOVES: [&[u16]; 4] = [
+    &[
+        450, 343, 162, 530, 324, 442, 402, 529, 340, 67, 441, 253, 9, 7, 8,
+    ],
+    &[
+        277, 335, 414, 492, 356, 393, 334, 387, 276, 527, 196, 401, 399, 428, 406, 304, 231,
+    ],
+    &[
+        20, 173, 282, 235, 257, 272, 215, 366, 143, 220, 202, 409, 264, 351, 352,
+    ],
+    &[
+        380, 388, 180, 495, 270, 271, 478, 472, 283, 200, 278, 289, 446, 214, 285,
+    ],
+];
+
 #[must_use]
 pub fn move_id_to_name(id: usize) -> &'static str {
     debug_assert!(id != 0);
     MOVE_NAMES.get(id - 1).unwrap_or(&"-----")
 }
+
+/// Looks a move up by name, ignoring case.
+#[must_use]
+pub fn move_name_to_id(name: &str) -> Option<u16> {
+    (1..)
+        .zip(MOVE_NAMES)
+        .find_map(|(id, move_name)| move_name.eq_ignore_ascii_case(name).then_some(id))
+}
+
+/// Looks a Pokémon up by name, ignoring case, returning its index in [`POKE_NAMES`].
+#[must_use]
+pub fn pokemon_name_to_index(name: &str) -> Option<usize> {
+    POKE_NAMES
+        .iter()
+        .position(|pokemon_name| pokemon_name.eq_ignore_ascii_case(name))
+}
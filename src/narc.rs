@@ -0,0 +1,438 @@
+use std::{fmt::Display, ops::Range};
+
+use crate::bytes::{self, UnexpectedEnd, read_bytes, read_u16, read_u32};
+
+const NARC_MAGIC: [u8; 4] = *b"NARC";
+const BTAF_MAGIC: [u8; 4] = *b"BTAF";
+const BTNF_MAGIC: [u8; 4] = *b"BTNF";
+const GMIF_MAGIC: [u8; 4] = *b"GMIF";
+
+const BYTE_ORDER_MARK: u16 = 0xfffe;
+const VERSION: u16 = 0x0100;
+const HEADER_LEN: u16 = 0x10;
+const SECTION_COUNT: u16 = 3;
+
+/// Name table of an archive whose files have no names: a lone root directory.
+const EMPTY_NAME_TABLE: [u8; 8] = [0x04, 0, 0, 0, 0, 0, 0x01, 0];
+
+const SECTION_HEADER_LEN: usize = 8;
+const FAT_ENTRY_LEN: usize = 8;
+const ALIGNMENT: usize = 4;
+const PADDING: u8 = 0xff;
+
+/// A Nitro archive, as found in the game's `a/x/y/z` data files.
+///
+/// The archive is made of a `NARC` header followed by three sections:
+/// - `BTAF`, the allocation table giving the start and end of each member file,
+/// - `BTNF`, the name table, kept as is,
+/// - `GMIF`, the image block holding the member files back to back.
+pub struct Narc<'a> {
+    data: &'a [u8],
+    name_table: &'a [u8],
+    entries: Vec<Range<usize>>,
+}
+
+impl<'a> Narc<'a> {
+    /// Raw content of the `BTNF` section, without its section header.
+    #[must_use]
+    pub const fn name_table(&self) -> &'a [u8] {
+        self.name_table
+    }
+
+    /// Absolute byte ranges of every member file inside the archive.
+    #[must_use]
+    pub fn entries(&self) -> &[Range<usize>] {
+        &self.entries
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &'a [u8]> {
+        let data = self.data;
+        self.entries.iter().map(move |range| &data[range.clone()])
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Narc<'a> {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        expect_magic(data, 0, NARC_MAGIC)?;
+        let header_len = usize::from(read_u16(data, 0xc)?);
+
+        let (fat, next) = section(data, header_len, BTAF_MAGIC)?;
+        let (name_table, next) = section(data, next, BTNF_MAGIC)?;
+        let image_start = next + SECTION_HEADER_LEN;
+        let (image, _) = section(data, next, GMIF_MAGIC)?;
+
+        let fat_start = header_len + SECTION_HEADER_LEN;
+        let file_count = usize::from(read_u16(data, fat_start)?);
+        if fat.len() < 4 + file_count * FAT_ENTRY_LEN {
+            return Err(Error::UnexpectedEnd(UnexpectedEnd {
+                offset: fat_start + fat.len(),
+                needed: 4 + file_count * FAT_ENTRY_LEN - fat.len(),
+            }));
+        }
+        let entries = (0..file_count)
+            .map(|index| {
+                let offset = fat_start + 4 + index * FAT_ENTRY_LEN;
+                let start = read_u32(data, offset)? as usize;
+                let end = read_u32(data, offset + 4)? as usize;
+                if start > end || end > image.len() {
+                    return Err(Error::InvalidEntry {
+                        index,
+                        offset,
+                        start,
+                        end,
+                        image_len: image.len(),
+                    });
+                }
+                Ok(image_start + start..image_start + end)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            data,
+            name_table,
+            entries,
+        })
+    }
+}
+
+/// Reasons an archive cannot be read, with the absolute byte offset where it went wrong.
+#[derive(Debug)]
+pub enum Error {
+    BadMagic {
+        offset: usize,
+        expected: [u8; 4],
+        found: [u8; 4],
+    },
+    UnexpectedEnd(UnexpectedEnd),
+    InvalidEntry {
+        index: usize,
+        offset: usize,
+        start: usize,
+        end: usize,
+        image_len: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadMagic {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} magic at offset {offset:#x}, found {}",
+                expected.escape_ascii(),
+                found.escape_ascii()
+            ),
+            Self::UnexpectedEnd(error) => write!(f, "{error}"),
+            Self::InvalidEntry {
+                index,
+                offset,
+                start,
+                end,
+                image_len,
+            } => write!(
+                f,
+                "allocation table entry {index} at offset {offset:#x} spans {start:#x}..{end:#x}, \
+                 outside of the {image_len:#x} bytes image block"
+            ),
+        }
+    }
+}
+
+impl From<UnexpectedEnd> for Error {
+    fn from(error: UnexpectedEnd) -> Self {
+        Self::UnexpectedEnd(error)
+    }
+}
+
+/// Reasons the members of an archive cannot be read.
+#[derive(Debug)]
+pub enum ArchiveError {
+    Narc(Error),
+    Empty,
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Narc(error) => write!(f, "invalid NARC: {error}"),
+            Self::Empty => write!(f, "the archive does not contain any file"),
+        }
+    }
+}
+
+/// Member file of an archive, along with the absolute offset it starts at.
+pub struct Member<'a> {
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+/// Reads the name table and the member files of an archive holding at least one file.
+pub fn members(data: &[u8]) -> Result<(&[u8], Vec<Member<'_>>), ArchiveError> {
+    let narc = Narc::try_from(data).map_err(ArchiveError::Narc)?;
+    let members: Vec<_> = narc
+        .entries()
+        .iter()
+        .zip(narc.files())
+        .map(|(range, data)| Member {
+            offset: range.start,
+            data,
+        })
+        .collect();
+    if members.is_empty() {
+        return Err(ArchiveError::Empty);
+    }
+    Ok((narc.name_table(), members))
+}
+
+/// What an archive indexed by Pokémon keeps as read around the member of each Pokémon: its name
+/// table, and its first member, which is not tied to any Pokémon.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Envelope {
+    name_table: Box<[u8]>,
+    first_member: Box<[u8]>,
+}
+
+impl Envelope {
+    /// Envelope of an archive whose files have no names.
+    #[must_use]
+    pub fn unnamed(first_member: &[u8]) -> Self {
+        Self {
+            name_table: Box::from(EMPTY_NAME_TABLE.as_slice()),
+            first_member: Box::from(first_member),
+        }
+    }
+
+    /// Splits an archive indexed by Pokémon into its envelope and the member of each Pokémon.
+    pub fn split(data: &[u8]) -> Result<(Self, Vec<Member<'_>>), ArchiveError> {
+        let (name_table, mut members) = members(data)?;
+        let first_member = members.remove(0);
+        let envelope = Self {
+            name_table: Box::from(name_table),
+            first_member: Box::from(first_member.data),
+        };
+        Ok((envelope, members))
+    }
+
+    /// Builds the archive back out of the member of each Pokémon.
+    #[must_use]
+    pub fn to_binary_format<F: AsRef<[u8]>>(&self, pokemons: &[F]) -> Vec<u8> {
+        let mut files = vec![&*self.first_member];
+        files.extend(pokemons.iter().map(AsRef::as_ref));
+        to_binary_format(&self.name_table, &files)
+    }
+}
+
+/// Builds a whole archive out of its name table and member files.
+///
+/// Every size and offset is computed from the given files: the header length, each `BTAF` entry
+/// and the `GMIF` size. Member files are padded with `0xFF` so each one starts 4-byte aligned.
+#[must_use]
+pub fn to_binary_format<F: AsRef<[u8]>>(name_table: &[u8], files: &[F]) -> Vec<u8> {
+    let mut fat = Vec::with_capacity(files.len() * FAT_ENTRY_LEN);
+    let mut image = Vec::new();
+    for file in files {
+        let file = file.as_ref();
+        fat.extend_from_slice(&to_u32(image.len()).to_le_bytes());
+        image.extend_from_slice(file);
+        fat.extend_from_slice(&to_u32(image.len()).to_le_bytes());
+        pad(&mut image);
+    }
+
+    let file_count = u16::try_from(files.len()).expect("NARC can hold at most 65535 files");
+    let mut fat_section = file_count.to_le_bytes().to_vec();
+    fat_section.extend_from_slice(&[0, 0]);
+    fat_section.extend_from_slice(&fat);
+
+    let mut name_table = name_table.to_vec();
+    pad(&mut name_table);
+
+    let mut sections = Vec::new();
+    push_section(&mut sections, BTAF_MAGIC, &fat_section);
+    push_section(&mut sections, BTNF_MAGIC, &name_table);
+    push_section(&mut sections, GMIF_MAGIC, &image);
+
+    let mut result = Vec::with_capacity(usize::from(HEADER_LEN) + sections.len());
+    result.extend_from_slice(&NARC_MAGIC);
+    result.extend_from_slice(&BYTE_ORDER_MARK.to_le_bytes());
+    result.extend_from_slice(&VERSION.to_le_bytes());
+    result.extend_from_slice(&to_u32(usize::from(HEADER_LEN) + sections.len()).to_le_bytes());
+    result.extend_from_slice(&HEADER_LEN.to_le_bytes());
+    result.extend_from_slice(&SECTION_COUNT.to_le_bytes());
+    result.extend_from_slice(&sections);
+    result
+}
+
+fn push_section(result: &mut Vec<u8>, magic: [u8; 4], content: &[u8]) {
+    result.extend_from_slice(&magic);
+    result.extend_from_slice(&to_u32(SECTION_HEADER_LEN + content.len()).to_le_bytes());
+    result.extend_from_slice(content);
+}
+
+fn pad(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(ALIGNMENT), PADDING);
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("NARC sizes fit in 32 bits")
+}
+
+/// Returns the content of the section starting at `offset`, along with the offset of the next one.
+fn section(data: &[u8], offset: usize, magic: [u8; 4]) -> Result<(&[u8], usize), Error> {
+    expect_magic(data, offset, magic)?;
+    let len = read_u32(data, offset + 4)? as usize;
+    let end = offset + len.max(SECTION_HEADER_LEN);
+    let content = bytes::slice(data, offset + SECTION_HEADER_LEN..end)?;
+    Ok((content, end))
+}
+
+fn expect_magic(data: &[u8], offset: usize, expected: [u8; 4]) -> Result<(), Error> {
+    let found = read_bytes(data, offset)?;
+    if found == expected {
+        Ok(())
+    } else {
+        Err(Error::BadMagic {
+            offset,
+            expected,
+            found,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME_TABLE: [u8; 8] = [0x04, 0, 0, 0, 0, 0, 0x01, 0];
+
+    /// Archive holding `files` back to back, each one 4-byte aligned, with an empty name table.
+    fn archive(files: &[&[u8]]) -> Vec<u8> {
+        let mut fat = Vec::new();
+        let mut image = Vec::new();
+        for file in files {
+            fat.extend_from_slice(&(image.len() as u32).to_le_bytes());
+            image.extend_from_slice(file);
+            fat.extend_from_slice(&(image.len() as u32).to_le_bytes());
+            image.resize(image.len().next_multiple_of(4), 0xff);
+        }
+        let mut sections = Vec::new();
+        let mut section = |magic: [u8; 4], content: &[u8]| {
+            sections.extend_from_slice(&magic);
+            sections.extend_from_slice(&(content.len() as u32 + 8).to_le_bytes());
+            sections.extend_from_slice(content);
+        };
+        let mut fat_section = (files.len() as u32).to_le_bytes().to_vec();
+        fat_section.extend_from_slice(&fat);
+        section(BTAF_MAGIC, &fat_section);
+        section(BTNF_MAGIC, &NAME_TABLE);
+        section(GMIF_MAGIC, &image);
+
+        let mut data = NARC_MAGIC.to_vec();
+        data.extend_from_slice(&[0xfe, 0xff, 0x00, 0x01]);
+        data.extend_from_slice(&(sections.len() as u32 + 0x10).to_le_bytes());
+        data.extend_from_slice(&[0x10, 0, 3, 0]);
+        data.extend_from_slice(&sections);
+        data
+    }
+
+    #[test]
+    fn member_files_are_read_from_the_allocation_table() {
+        let files: [&[u8]; 3] = [&[1, 2, 3, 4, 5], &[], &[6, 7]];
+        let data = archive(&files);
+        let narc = Narc::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(narc.files().collect::<Vec<_>>(), files);
+        assert_eq!(narc.entries()[0].start, 0x10 + 8 + 4 + 3 * 8 + 16 + 8);
+        assert_eq!(narc.entries()[2].start, narc.entries()[0].start + 8);
+    }
+
+    #[test]
+    fn written_archives_follow_the_layout() {
+        let files: [&[u8]; 3] = [&[1, 2, 3, 4, 5], &[], &[6, 7]];
+        assert_eq!(to_binary_format(&NAME_TABLE, &files), archive(&files));
+    }
+
+    #[test]
+    fn written_archives_read_back() {
+        // Member files of every length modulo the alignment, including an empty one.
+        let files: Vec<Vec<u8>> = (0..9).map(|index| vec![index as u8; index]).collect();
+        let data = to_binary_format(&NAME_TABLE, &files);
+        let narc = Narc::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(narc.name_table(), NAME_TABLE);
+        assert_eq!(narc.files().collect::<Vec<_>>(), files);
+        assert_eq!(read_u32(&data, 8).unwrap() as usize, data.len());
+    }
+
+    #[test]
+    fn split_archives_are_written_back_unchanged() {
+        let files: Vec<Vec<u8>> = (0..9).map(|index| vec![index as u8; index]).collect();
+        let data = to_binary_format(&NAME_TABLE, &files);
+        let (envelope, members) = Envelope::split(&data).unwrap();
+        let pokemons: Vec<&[u8]> = members.iter().map(|member| member.data).collect();
+
+        assert_eq!(pokemons, files[1..]);
+        assert_eq!(envelope.to_binary_format(&pokemons), data);
+    }
+
+    #[test]
+    fn members_start_at_their_offset() {
+        let data = archive(&[&[1, 2, 3, 4, 5], &[], &[6, 7]]);
+        let (name_table, members) = members(&data).unwrap();
+
+        assert_eq!(name_table, NAME_TABLE);
+        for member in members {
+            assert_eq!(&data[member.offset..][..member.data.len()], member.data);
+        }
+    }
+
+    #[test]
+    fn archives_without_members_are_rejected() {
+        let data = to_binary_format::<&[u8]>(&NAME_TABLE, &[]);
+        assert!(matches!(members(&data), Err(ArchiveError::Empty)));
+        assert!(matches!(
+            Envelope::split(b"NCGR"),
+            Err(ArchiveError::Narc(Error::BadMagic { .. }))
+        ));
+    }
+
+    #[test]
+    fn entries_past_the_image_are_rejected() {
+        let mut data = archive(&[&[1, 2, 3, 4]]);
+        data[0x10 + 8 + 8..][..4].copy_from_slice(&0x100_u32.to_le_bytes());
+        assert!(matches!(
+            Narc::try_from(data.as_slice()),
+            Err(Error::InvalidEntry {
+                index: 0,
+                offset: 0x1c,
+                end: 0x100,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn other_formats_are_rejected() {
+        let mut data = archive(&[&[1, 2, 3, 4]]);
+        data[..4].copy_from_slice(b"NCGR");
+        assert!(matches!(
+            Narc::try_from(data.as_slice()),
+            Err(Error::BadMagic { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_archives_are_rejected() {
+        let data = archive(&[&[1, 2, 3, 4]]);
+        let len = data.len() - 4;
+        assert!(matches!(
+            Narc::try_from(&data[..len]),
+            Err(Error::UnexpectedEnd(UnexpectedEnd { offset, needed: 4 })) if offset == len
+        ));
+    }
+}
@@ -0,0 +1,642 @@
+use std::{fmt::Display, ops::Range};
+
+use crate::bytes::{self, UnexpectedEnd, read_bytes, read_u16, read_u32};
+
+const GAME_CODE_OFFSET: usize = 0x0c;
+const UNIT_CODE_OFFSET: usize = 0x12;
+const CAPACITY_OFFSET: usize = 0x14;
+const FNT_OFFSET: usize = 0x40;
+const FAT_OFFSET: usize = 0x48;
+const USED_SIZE_OFFSET: usize = 0x80;
+const HEADER_CRC_OFFSET: usize = 0x15e;
+const DSI_USED_SIZE_OFFSET: usize = 0x210;
+
+/// Header fields holding the offset of a block stored in the image.
+const BLOCK_OFFSETS: [usize; 7] = [0x20, 0x30, FNT_OFFSET, FAT_OFFSET, 0x50, 0x58, 0x68];
+/// Same as [`BLOCK_OFFSETS`] for the DSi-specific blocks, used when the unit code is not 0.
+const DSI_BLOCK_OFFSETS: [usize; 2] = [0x1c0, 0x1d0];
+
+/// Files are aligned on 512 bytes, and gaps between them filled with `0xFF`.
+const ALIGNMENT: usize = 0x200;
+const PADDING: u8 = 0xff;
+const MIN_CAPACITY: usize = 0x20000;
+
+const FAT_ENTRY_LEN: usize = 8;
+const FNT_DIRECTORY_LEN: usize = 8;
+const ROOT_DIRECTORY: u16 = 0xf000;
+
+/// The generation 5 games, told apart by the first three letters of their game code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Game {
+    BlackWhite,
+    Black2White2,
+}
+
+impl Game {
+    #[must_use]
+    pub fn from_code(code: [u8; 4]) -> Option<Self> {
+        match &code[..3] {
+            b"IRA" | b"IRB" => Some(Self::BlackWhite),
+            b"IRD" | b"IRE" => Some(Self::Black2White2),
+            _ => None,
+        }
+    }
+
+    /// Path of the level-up learnset NARC in the ROM filesystem.
+    #[must_use]
+    pub const fn learnset_path(self) -> &'static str {
+        match self {
+            Self::BlackWhite | Self::Black2White2 => "a/0/1/8",
+        }
+    }
+
+    /// Path of the personal data NARC in the ROM filesystem.
+    #[must_use]
+    pub const fn personal_path(self) -> &'static str {
+        match self {
+            Self::BlackWhite | Self::Black2White2 => "a/0/1/6",
+        }
+    }
+
+    /// Path of the evolution NARC in the ROM filesystem.
+    #[must_use]
+    pub const fn evolution_path(self) -> &'static str {
+        match self {
+            Self::BlackWhite | Self::Black2White2 => "a/0/1/9",
+        }
+    }
+
+    /// Path of the move data NARC in the ROM filesystem.
+    #[must_use]
+    pub const fn move_data_path(self) -> &'static str {
+        match self {
+            Self::BlackWhite | Self::Black2White2 => "a/0/2/1",
+        }
+    }
+
+    /// Path of the egg move NARC in the ROM filesystem.
+    #[must_use]
+    pub const fn egg_move_path(self) -> &'static str {
+        match self {
+            Self::BlackWhite => "a/1/2/3",
+            Self::Black2White2 => "a/1/2/4",
+        }
+    }
+}
+
+/// A Nintendo DS cartridge image.
+///
+/// Files are found through the file name table (FNT), which maps paths to file ids, and the file
+/// allocation table (FAT), which maps file ids to their location in the image.
+pub struct Rom<'a> {
+    data: &'a [u8],
+    game: Game,
+    fnt: &'a [u8],
+    fat: &'a [u8],
+    fat_offset: usize,
+}
+
+impl<'a> Rom<'a> {
+    #[must_use]
+    pub const fn game(&self) -> Game {
+        self.game
+    }
+
+    /// Absolute byte range of the file at `path`, such as `a/0/1/8`.
+    pub fn file_range(&self, path: &'static str) -> Result<Range<usize>, Error> {
+        let id = self.file_id(path)?;
+        let offset = usize::from(id) * FAT_ENTRY_LEN;
+        let start = read_u32(self.fat, offset)? as usize;
+        let end = read_u32(self.fat, offset + 4)? as usize;
+        if start > end || end > self.data.len() {
+            return Err(Error::InvalidFile {
+                path,
+                start,
+                end,
+                rom_len: self.data.len(),
+            });
+        }
+        Ok(start..end)
+    }
+
+    pub fn file(&self, path: &'static str) -> Result<&'a [u8], Error> {
+        self.file_range(path).map(|range| &self.data[range])
+    }
+
+    /// Builds a copy of the image where the file at `path` is replaced by `contents`.
+    ///
+    /// The file is rewritten in place when it fits before the next block. Otherwise everything
+    /// after it is moved forward, up to the end of the image including the data past the used
+    /// size such as DSi blocks, and the allocation table and header offsets are updated to
+    /// follow. The header size fields and CRC are updated in both cases.
+    pub fn with_file(&self, path: &'static str, contents: &[u8]) -> Result<Vec<u8>, Error> {
+        let id = usize::from(self.file_id(path)?);
+        let range = self.file_range(path)?;
+        let used_size = (read_u32(self.data, USED_SIZE_OFFSET)? as usize).min(self.data.len());
+        let block_offsets = self.block_offsets()?;
+
+        let next_block = self
+            .file_starts()
+            .chain(block_offsets.iter().map(|&(_, offset)| offset))
+            .filter(|&start| start >= range.end && start != range.start)
+            .min()
+            .unwrap_or(used_size)
+            .max(range.end);
+        let new_end = range.start + contents.len();
+        let shift = new_end
+            .next_multiple_of(ALIGNMENT)
+            .saturating_sub(next_block);
+        let shift = if new_end <= next_block { 0 } else { shift };
+
+        let mut result = Vec::with_capacity(self.data.len() + shift);
+        result.extend_from_slice(&self.data[..range.start]);
+        result.extend_from_slice(contents);
+        result.resize(next_block + shift, PADDING);
+        result.extend_from_slice(&self.data[next_block..]);
+
+        let shifted = |offset: usize| {
+            if offset >= next_block {
+                offset + shift
+            } else {
+                offset
+            }
+        };
+        for (field, offset) in block_offsets {
+            write_u32(&mut result, field, shifted(offset));
+        }
+        let fat_start = shifted(self.fat_offset);
+        for (index, start) in self.file_starts().enumerate() {
+            let entry = fat_start + index * FAT_ENTRY_LEN;
+            let end = read_u32(self.fat, index * FAT_ENTRY_LEN + 4)? as usize;
+            if index == id {
+                write_u32(&mut result, entry, range.start);
+                write_u32(&mut result, entry + 4, new_end);
+            } else if start >= next_block {
+                write_u32(&mut result, entry, start + shift);
+                write_u32(&mut result, entry + 4, end + shift);
+            }
+        }
+
+        write_u32(
+            &mut result,
+            USED_SIZE_OFFSET,
+            used_size.max(next_block) + shift,
+        );
+        if self.is_dsi() {
+            let dsi_used_size = read_u32(self.data, DSI_USED_SIZE_OFFSET)? as usize;
+            write_u32(&mut result, DSI_USED_SIZE_OFFSET, shifted(dsi_used_size));
+        }
+        let mut capacity = self.data[CAPACITY_OFFSET];
+        loop {
+            match chip_size(capacity) {
+                Some(size) if size >= result.len() => break,
+                Some(_) => capacity += 1,
+                None => {
+                    return Err(Error::InvalidCapacity {
+                        capacity: self.data[CAPACITY_OFFSET],
+                        rom_len: result.len(),
+                    });
+                }
+            }
+        }
+        result[CAPACITY_OFFSET] = capacity;
+        let crc = crc16(&result[..HEADER_CRC_OFFSET]);
+        result[HEADER_CRC_OFFSET..HEADER_CRC_OFFSET + 2].copy_from_slice(&crc.to_le_bytes());
+        Ok(result)
+    }
+
+    fn file_starts(&self) -> impl Iterator<Item = usize> {
+        self.fat
+            .as_chunks::<FAT_ENTRY_LEN>()
+            .0
+            .iter()
+            .map(|entry| u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as usize)
+    }
+
+    /// Header fields pointing into the image, along with the offset they hold.
+    fn block_offsets(&self) -> Result<Vec<(usize, usize)>, UnexpectedEnd> {
+        let dsi_offsets: &[usize] = if self.is_dsi() {
+            &DSI_BLOCK_OFFSETS
+        } else {
+            &[]
+        };
+        BLOCK_OFFSETS
+            .iter()
+            .chain(dsi_offsets)
+            .map(|&field| Ok((field, read_u32(self.data, field)? as usize)))
+            .filter(|block| !matches!(block, Ok((_, 0))))
+            .collect()
+    }
+
+    fn is_dsi(&self) -> bool {
+        self.data[UNIT_CODE_OFFSET] != 0
+    }
+
+    fn file_id(&self, path: &'static str) -> Result<u16, Error> {
+        let mut directory = ROOT_DIRECTORY;
+        let mut components = path.split('/').peekable();
+        while let Some(component) = components.next() {
+            let is_last = components.peek().is_none();
+            match self.find_entry(directory, component)? {
+                Some(Entry::File(id)) if is_last => return Ok(id),
+                Some(Entry::Directory(id)) if !is_last => directory = id,
+                _ => break,
+            }
+        }
+        Err(Error::FileNotFound(path))
+    }
+
+    /// Looks `name` up among the direct children of `directory`.
+    fn find_entry(&self, directory: u16, name: &str) -> Result<Option<Entry>, Error> {
+        let directory_offset = usize::from(directory & 0x0fff) * FNT_DIRECTORY_LEN;
+        let mut offset = read_u32(self.fnt, directory_offset)? as usize;
+        let mut file_id = read_u16(self.fnt, directory_offset + 4)?;
+
+        loop {
+            let [kind] = read_bytes(self.fnt, offset)?;
+            if kind == 0 {
+                return Ok(None);
+            }
+            let name_len = usize::from(kind & 0x7f);
+            let entry_name = bytes::slice(self.fnt, offset + 1..offset + 1 + name_len)?;
+            offset += 1 + name_len;
+
+            let is_directory = kind & 0x80 != 0;
+            let entry = if is_directory {
+                let id = read_u16(self.fnt, offset)?;
+                offset += 2;
+                Entry::Directory(id)
+            } else {
+                file_id += 1;
+                Entry::File(file_id - 1)
+            };
+            if entry_name == name.as_bytes() {
+                return Ok(Some(entry));
+            }
+        }
+    }
+}
+
+enum Entry {
+    File(u16),
+    Directory(u16),
+}
+
+impl<'a> TryFrom<&'a [u8]> for Rom<'a> {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let code = read_bytes(data, GAME_CODE_OFFSET)?;
+        let game = Game::from_code(code).ok_or(Error::UnknownGame(code))?;
+        let fnt = table(data, FNT_OFFSET)?;
+        let fat = table(data, FAT_OFFSET)?;
+        Ok(Self {
+            data,
+            game,
+            fnt: &data[fnt],
+            fat: &data[fat.clone()],
+            fat_offset: fat.start,
+        })
+    }
+}
+
+/// CRC-16/MODBUS, used for the header checksum.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, &byte| {
+        (0..8).fold(crc ^ u16::from(byte), |crc, _| {
+            if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xa001
+            }
+        })
+    })
+}
+
+/// Size of the chip a device capacity stands for, unless it does not fit in a `usize`.
+fn chip_size(capacity: u8) -> Option<usize> {
+    let capacity = u32::from(capacity);
+    MIN_CAPACITY
+        .checked_shl(capacity)
+        .filter(|size| size >> capacity == MIN_CAPACITY)
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: usize) {
+    let value = u32::try_from(value).expect("ROM offsets fit in 32 bits");
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Range of the table whose offset and size are stored in the header at `header_offset`.
+fn table(data: &[u8], header_offset: usize) -> Result<Range<usize>, UnexpectedEnd> {
+    let start = read_u32(data, header_offset)? as usize;
+    let len = read_u32(data, header_offset + 4)? as usize;
+    bytes::slice(data, start..start + len)?;
+    Ok(start..start + len)
+}
+
+/// Reasons a ROM image cannot be read.
+#[derive(Debug)]
+pub enum Error {
+    UnexpectedEnd(UnexpectedEnd),
+    UnknownGame([u8; 4]),
+    FileNotFound(&'static str),
+    InvalidFile {
+        path: &'static str,
+        start: usize,
+        end: usize,
+        rom_len: usize,
+    },
+    /// The device capacity in the header cannot describe a chip large enough for the image.
+    InvalidCapacity {
+        capacity: u8,
+        rom_len: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd(error) => write!(f, "{error}"),
+            Self::UnknownGame(code) => write!(
+                f,
+                "game code {} is not a generation 5 game",
+                code.escape_ascii()
+            ),
+            Self::FileNotFound(path) => write!(f, "{path} is not in the ROM filesystem"),
+            Self::InvalidFile {
+                path,
+                start,
+                end,
+                rom_len,
+            } => write!(
+                f,
+                "{path} spans {start:#x}..{end:#x}, outside of the {rom_len:#x} bytes ROM"
+            ),
+            Self::InvalidCapacity { capacity, rom_len } => write!(
+                f,
+                "device capacity {capacity} cannot describe a chip holding {rom_len:#x} bytes"
+            ),
+        }
+    }
+}
+
+impl From<UnexpectedEnd> for Error {
+    fn from(error: UnexpectedEnd) -> Self {
+        Self::UnexpectedEnd(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER_LEN: usize = 0x4000;
+    const ARM9: [u8; 0x100] = [0x99; 0x100];
+    const DSI_BLOCK: [u8; 0x300] = [0xd5; 0x300];
+    const PATHS: [&str; 5] = ["a/0/1/6", "a/0/1/8", "a/0/1/9", "a/0/2/1", "z"];
+
+    #[derive(Default)]
+    struct Directory {
+        /// Name and index in the files given to [`build_rom`].
+        files: Vec<(String, usize)>,
+        /// Name and index of the child directories.
+        directories: Vec<(String, usize)>,
+        parent: usize,
+    }
+
+    fn contents(index: usize) -> Vec<u8> {
+        vec![index as u8 + 1; 0x180 + index * 0x40]
+    }
+
+    /// Builds a Black and White image holding the files at [`PATHS`], with an ARM9 block before
+    /// the file tables and, for a DSi-enhanced image, a DSi block past the used size.
+    fn build_rom(files: &[Vec<u8>], dsi: bool) -> Vec<u8> {
+        let mut directories = vec![Directory::default()];
+        for (index, path) in PATHS.iter().enumerate() {
+            let mut components: Vec<&str> = path.split('/').collect();
+            let name = components.pop().unwrap();
+            let mut current = 0;
+            for component in components {
+                let existing = directories[current]
+                    .directories
+                    .iter()
+                    .find(|(name, _)| name == component);
+                current = match existing {
+                    Some(&(_, id)) => id,
+                    None => {
+                        directories.push(Directory {
+                            parent: current,
+                            ..Directory::default()
+                        });
+                        let id = directories.len() - 1;
+                        directories[current]
+                            .directories
+                            .push((component.to_string(), id));
+                        id
+                    }
+                };
+            }
+            directories[current].files.push((name.to_string(), index));
+        }
+
+        // File ids are given directory by directory, so `order` maps them to the files.
+        let mut order = Vec::new();
+        let mut fnt = Vec::new();
+        let mut subtables = Vec::new();
+        for (id, directory) in directories.iter().enumerate() {
+            let parent = if id == 0 {
+                directories.len()
+            } else {
+                0xf000 | directory.parent
+            };
+            fnt.extend_from_slice(&(directories.len() * 8 + subtables.len()).to_le_bytes()[..4]);
+            fnt.extend_from_slice(&(order.len() as u16).to_le_bytes());
+            fnt.extend_from_slice(&(parent as u16).to_le_bytes());
+            for (name, index) in &directory.files {
+                subtables.push(name.len() as u8);
+                subtables.extend_from_slice(name.as_bytes());
+                order.push(*index);
+            }
+            for (name, child) in &directory.directories {
+                subtables.push(0x80 | name.len() as u8);
+                subtables.extend_from_slice(name.as_bytes());
+                subtables.extend_from_slice(&(0xf000 | *child as u16).to_le_bytes());
+            }
+            subtables.push(0);
+        }
+        fnt.extend_from_slice(&subtables);
+
+        let align = |rom: &mut Vec<u8>| rom.resize(rom.len().next_multiple_of(ALIGNMENT), PADDING);
+        let mut rom = vec![0; HEADER_LEN];
+        rom[GAME_CODE_OFFSET..GAME_CODE_OFFSET + 4].copy_from_slice(b"IRBO");
+        let len = rom.len();
+        write_u32(&mut rom, 0x20, len);
+        rom.extend_from_slice(&ARM9);
+        align(&mut rom);
+        let len = rom.len();
+        write_u32(&mut rom, FNT_OFFSET, len);
+        write_u32(&mut rom, FNT_OFFSET + 4, fnt.len());
+        rom.extend_from_slice(&fnt);
+        align(&mut rom);
+        let fat_offset = rom.len();
+        write_u32(&mut rom, FAT_OFFSET, fat_offset);
+        write_u32(&mut rom, FAT_OFFSET + 4, order.len() * FAT_ENTRY_LEN);
+        rom.resize(fat_offset + order.len() * FAT_ENTRY_LEN, 0);
+        align(&mut rom);
+        for (id, &index) in order.iter().enumerate() {
+            let start = rom.len();
+            rom.extend_from_slice(&files[index]);
+            write_u32(&mut rom, fat_offset + id * FAT_ENTRY_LEN, start);
+            let len = rom.len();
+            write_u32(&mut rom, fat_offset + id * FAT_ENTRY_LEN + 4, len);
+            align(&mut rom);
+        }
+        let len = rom.len();
+        write_u32(&mut rom, USED_SIZE_OFFSET, len);
+        if dsi {
+            rom[UNIT_CODE_OFFSET] = 2;
+            let len = rom.len();
+            write_u32(&mut rom, DSI_BLOCK_OFFSETS[0], len);
+            rom.extend_from_slice(&DSI_BLOCK);
+            let len = rom.len();
+            write_u32(&mut rom, DSI_USED_SIZE_OFFSET, len);
+        } else {
+            rom.resize(rom.len() + 0x400, PADDING);
+        }
+        let crc = crc16(&rom[..HEADER_CRC_OFFSET]);
+        rom[HEADER_CRC_OFFSET..HEADER_CRC_OFFSET + 2].copy_from_slice(&crc.to_le_bytes());
+        rom
+    }
+
+    fn files() -> Vec<Vec<u8>> {
+        (0..PATHS.len()).map(contents).collect()
+    }
+
+    fn dsi_block(rom: &[u8]) -> &[u8] {
+        let start = read_u32(rom, DSI_BLOCK_OFFSETS[0]).unwrap() as usize;
+        &rom[start..start + DSI_BLOCK.len()]
+    }
+
+    fn assert_header_crc(rom: &[u8]) {
+        let crc = read_u16(rom, HEADER_CRC_OFFSET).unwrap();
+        assert_eq!(crc, crc16(&rom[..HEADER_CRC_OFFSET]));
+    }
+
+    #[test]
+    fn rewriting_files_unchanged_gives_back_the_same_image() {
+        for dsi in [false, true] {
+            let data = build_rom(&files(), dsi);
+            let rom = Rom::try_from(data.as_slice()).unwrap();
+            for path in PATHS {
+                let rewritten = rom.with_file(path, rom.file(path).unwrap()).unwrap();
+                assert!(rewritten == data, "rewriting {path} changed the image");
+            }
+        }
+    }
+
+    #[test]
+    fn growing_a_file_moves_the_following_files_and_blocks() {
+        for dsi in [false, true] {
+            let data = build_rom(&files(), dsi);
+            let rom = Rom::try_from(data.as_slice()).unwrap();
+            let grown = vec![0xab; 0x500];
+            let result = rom.with_file("a/0/1/8", &grown).unwrap();
+            let edited = Rom::try_from(result.as_slice()).unwrap();
+
+            for (path, original) in PATHS.into_iter().zip(files()) {
+                let expected = if path == "a/0/1/8" { &grown } else { &original };
+                assert_eq!(edited.file(path).unwrap(), expected.as_slice(), "{path}");
+                assert_eq!(edited.file_range(path).unwrap().start % ALIGNMENT, 0);
+            }
+            let shift = result.len() - data.len();
+            assert_eq!(shift, 0x400);
+            assert_eq!(
+                read_u32(&result, USED_SIZE_OFFSET).unwrap(),
+                read_u32(&data, USED_SIZE_OFFSET).unwrap() + shift as u32
+            );
+            if dsi {
+                assert_eq!(dsi_block(&result), DSI_BLOCK);
+                assert_eq!(
+                    read_u32(&result, DSI_USED_SIZE_OFFSET).unwrap() as usize,
+                    result.len()
+                );
+            } else {
+                assert!(
+                    result[data.len() - 0x400 + shift..]
+                        .iter()
+                        .all(|&b| b == PADDING)
+                );
+            }
+            assert_eq!(&result[HEADER_LEN..HEADER_LEN + ARM9.len()], ARM9);
+            assert_header_crc(&result);
+        }
+    }
+
+    #[test]
+    fn shrinking_a_file_keeps_the_others_in_place() {
+        for dsi in [false, true] {
+            let data = build_rom(&files(), dsi);
+            let rom = Rom::try_from(data.as_slice()).unwrap();
+            let result = rom.with_file("a/0/1/8", &[1, 2, 3]).unwrap();
+            let edited = Rom::try_from(result.as_slice()).unwrap();
+
+            assert_eq!(result.len(), data.len());
+            assert_eq!(edited.file("a/0/1/8").unwrap(), [1, 2, 3]);
+            for path in PATHS.into_iter().filter(|&path| path != "a/0/1/8") {
+                assert_eq!(
+                    edited.file_range(path).unwrap(),
+                    rom.file_range(path).unwrap()
+                );
+                assert_eq!(edited.file(path).unwrap(), rom.file(path).unwrap());
+            }
+            if dsi {
+                assert_eq!(dsi_block(&result), DSI_BLOCK);
+            }
+            assert_header_crc(&result);
+        }
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let data = build_rom(&files(), false);
+        let rom = Rom::try_from(data.as_slice()).unwrap();
+        for path in ["a/0/1/7", "a/0/1", "a/0/1/8/0", "b"] {
+            assert!(
+                matches!(rom.file(path), Err(Error::FileNotFound(_))),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_device_capacity_follows_the_image_size() {
+        let data = build_rom(&files(), false);
+        let rom = Rom::try_from(data.as_slice()).unwrap();
+        let result = rom.with_file(PATHS[1], &[0x42; 0x30000]).unwrap();
+
+        let capacity = result[CAPACITY_OFFSET];
+        assert!(MIN_CAPACITY << (capacity - 1) < result.len());
+        assert!(MIN_CAPACITY << capacity >= result.len());
+    }
+
+    #[test]
+    fn capacities_past_the_address_space_are_reported() {
+        for capacity in [47, 0x40, 0xff] {
+            let mut data = build_rom(&files(), false);
+            data[CAPACITY_OFFSET] = capacity;
+            let rom = Rom::try_from(data.as_slice()).unwrap();
+            assert!(
+                matches!(
+                    rom.with_file(PATHS[1], &[0x42; 4]),
+                    Err(Error::InvalidCapacity { capacity: found, .. }) if found == capacity
+                ),
+                "{capacity}"
+            );
+        }
+    }
+
+    #[test]
+    fn crc16_matches_the_modbus_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x4b37);
+    }
+}
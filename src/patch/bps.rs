@@ -0,0 +1,393 @@
+//! Beat patches: a stream of actions copying from the source or the patch, with CRC-32 checks of
+//! the source, the target and the patch itself.
+
+use std::collections::HashMap;
+
+use super::{Error, Reader, crc32};
+
+pub const MAGIC: [u8; 4] = *b"BPS1";
+const FOOTER_LEN: usize = 12;
+
+const SOURCE_READ: usize = 0;
+const TARGET_READ: usize = 1;
+const SOURCE_COPY: usize = 2;
+const TARGET_COPY: usize = 3;
+
+/// Matches shorter than this are written as new bytes rather than copied.
+const MIN_MATCH: usize = 8;
+
+#[must_use]
+pub fn create(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = MAGIC.to_vec();
+    write_number(&mut patch, source.len());
+    write_number(&mut patch, target.len());
+    write_number(&mut patch, 0);
+
+    // Files moved around in a ROM stay aligned, so indexing aligned blocks of the source is
+    // enough to find them back, without indexing every offset of a large image.
+    let stride = if source.len() <= 1 << 24 { 4 } else { 0x200 };
+    let mut index = HashMap::new();
+    for offset in (0..source.len().saturating_sub(MIN_MATCH)).step_by(stride) {
+        index
+            .entry(&source[offset..offset + MIN_MATCH])
+            .or_insert(offset);
+    }
+
+    let mut encoder = Encoder::default();
+    let mut last_copy = None;
+    let mut offset = 0;
+    while offset < target.len() {
+        let rest = &target[offset..];
+        let same_place = match_len(source.get(offset..).unwrap_or_default(), rest);
+        if same_place >= MIN_MATCH {
+            encoder.source_read(&mut patch, same_place);
+            offset += same_place;
+            continue;
+        }
+
+        let moved = last_copy
+            .into_iter()
+            .chain(
+                (offset.is_multiple_of(stride) && rest.len() >= MIN_MATCH)
+                    .then(|| index.get(&rest[..MIN_MATCH]).copied())
+                    .flatten(),
+            )
+            .map(|start: usize| (start, match_len(&source[start..], rest)))
+            .find(|&(_, len)| len >= MIN_MATCH);
+        if let Some((start, len)) = moved {
+            encoder.source_copy(&mut patch, start, len);
+            last_copy = Some(start + len).filter(|&end| end < source.len());
+            offset += len;
+            continue;
+        }
+
+        encoder.pending.push(target[offset]);
+        offset += 1;
+    }
+    encoder.flush(&mut patch);
+
+    patch.extend_from_slice(&crc32(source).to_le_bytes());
+    patch.extend_from_slice(&crc32(target).to_le_bytes());
+    let patch_crc = crc32(&patch);
+    patch.extend_from_slice(&patch_crc.to_le_bytes());
+    patch
+}
+
+#[derive(Default)]
+struct Encoder {
+    /// New bytes waiting to be written as a single target read action.
+    pending: Vec<u8>,
+    source_relative_offset: usize,
+}
+
+impl Encoder {
+    fn flush(&mut self, patch: &mut Vec<u8>) {
+        if !self.pending.is_empty() {
+            write_action(patch, TARGET_READ, self.pending.len());
+            patch.append(&mut self.pending);
+        }
+    }
+
+    fn source_read(&mut self, patch: &mut Vec<u8>, len: usize) {
+        self.flush(patch);
+        write_action(patch, SOURCE_READ, len);
+    }
+
+    fn source_copy(&mut self, patch: &mut Vec<u8>, start: usize, len: usize) {
+        self.flush(patch);
+        write_action(patch, SOURCE_COPY, len);
+        write_signed(
+            patch,
+            start.cast_signed() - self.source_relative_offset.cast_signed(),
+        );
+        self.source_relative_offset = start + len;
+    }
+}
+
+pub fn apply(patch: &[u8], source: &[u8]) -> Result<Vec<u8>, Error> {
+    let Some(body_len) = patch.len().checked_sub(FOOTER_LEN) else {
+        return Err(Error::Truncated {
+            offset: patch.len(),
+        });
+    };
+    let footer = |index: usize| {
+        let start = body_len + index * 4;
+        u32::from_le_bytes(patch[start..start + 4].try_into().unwrap())
+    };
+    check("patch", footer(2), crc32(&patch[..body_len + 8]))?;
+    check("source", footer(0), crc32(source))?;
+
+    let mut reader = Reader {
+        patch: &patch[..body_len],
+        offset: MAGIC.len(),
+    };
+    let source_len = reader.number()?;
+    if source_len != source.len() {
+        return Err(Error::SourceSize {
+            expected: source_len,
+            found: source.len(),
+        });
+    }
+    let target_len = reader.number()?;
+    let metadata_len = reader.number()?;
+    reader.take(metadata_len)?;
+
+    // The length comes from the patch, so it only bounds the allocation once checked against
+    // what the actions can produce.
+    let mut target = Vec::with_capacity(target_len.min(source.len() + patch.len()));
+    let mut source_relative_offset = 0;
+    let mut target_relative_offset = 0;
+    while reader.offset < reader.patch.len() {
+        let action_offset = reader.offset;
+        let out_of_bounds = || Error::OutOfBounds {
+            offset: action_offset,
+        };
+        let data = reader.number()?;
+        let len = (data >> 2) + 1;
+        if len > target_len - target.len() {
+            return Err(out_of_bounds());
+        }
+        match data & 3 {
+            SOURCE_READ => {
+                let bytes = range(source, target.len(), len).ok_or_else(out_of_bounds)?;
+                target.extend_from_slice(bytes);
+            }
+            TARGET_READ => target.extend_from_slice(reader.take(len)?),
+            SOURCE_COPY => {
+                source_relative_offset = reader
+                    .relative(source_relative_offset)
+                    .ok_or_else(out_of_bounds)?;
+                let bytes = range(source, source_relative_offset, len).ok_or_else(out_of_bounds)?;
+                target.extend_from_slice(bytes);
+                source_relative_offset += len;
+            }
+            TARGET_COPY => {
+                target_relative_offset = reader
+                    .relative(target_relative_offset)
+                    .filter(|&start| start < target.len())
+                    .ok_or_else(out_of_bounds)?;
+                // The copied range may overlap the bytes being written, so copy byte by byte.
+                for _ in 0..len {
+                    target.push(target[target_relative_offset]);
+                    target_relative_offset += 1;
+                }
+            }
+            _ => unreachable!("actions are two bits long"),
+        }
+    }
+
+    if target.len() != target_len {
+        return Err(Error::Truncated {
+            offset: reader.offset,
+        });
+    }
+    check("target", footer(1), crc32(&target))?;
+    Ok(target)
+}
+
+/// The `len` bytes of `data` from `start`, unless they do not fit or the end overflows.
+fn range(data: &[u8], start: usize, len: usize) -> Option<&[u8]> {
+    data.get(start..start.checked_add(len)?)
+}
+
+const fn check(what: &'static str, expected: u32, found: u32) -> Result<(), Error> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(Error::Checksum {
+            what,
+            expected,
+            found,
+        })
+    }
+}
+
+fn match_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn write_action(patch: &mut Vec<u8>, action: usize, len: usize) {
+    write_number(patch, ((len - 1) << 2) | action);
+}
+
+fn write_signed(patch: &mut Vec<u8>, value: isize) {
+    write_number(patch, (value.unsigned_abs() << 1) | usize::from(value < 0));
+}
+
+/// Writes a variable-length number, 7 bits at a time with the last byte flagged.
+fn write_number(patch: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let low = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            patch.push(0x80 | low);
+            return;
+        }
+        patch.push(low);
+        value -= 1;
+    }
+}
+
+impl Reader<'_> {
+    fn number(&mut self) -> Result<usize, Error> {
+        let mut value: usize = 0;
+        let mut shift: usize = 1;
+        loop {
+            let byte = self.take(1)?[0];
+            value = usize::from(byte & 0x7f)
+                .checked_mul(shift)
+                .and_then(|low| value.checked_add(low))
+                .ok_or(Error::Truncated {
+                    offset: self.offset,
+                })?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_shl(7).ok_or(Error::Truncated {
+                offset: self.offset,
+            })?;
+            value = value.checked_add(shift).ok_or(Error::Truncated {
+                offset: self.offset,
+            })?;
+        }
+    }
+
+    /// Reads a signed displacement and applies it to `base`.
+    fn relative(&mut self, base: usize) -> Option<usize> {
+        let data = self.number().ok()?;
+        let distance = data >> 1;
+        if data & 1 == 0 {
+            base.checked_add(distance)
+        } else {
+            base.checked_sub(distance)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(len: usize) -> Vec<u8> {
+        (0..len).map(|offset| (offset * 7 % 251) as u8).collect()
+    }
+
+    /// Patch running `actions` over `source`, declaring a `target_len` bytes result.
+    fn crafted(source: &[u8], target_len: usize, actions: &[u8]) -> Vec<u8> {
+        let mut patch = MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target_len);
+        write_number(&mut patch, 0);
+        patch.extend_from_slice(actions);
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&0_u32.to_le_bytes());
+        let patch_crc = crc32(&patch);
+        patch.extend_from_slice(&patch_crc.to_le_bytes());
+        patch
+    }
+
+    fn round_trip(source: &[u8], target: &[u8]) -> Vec<u8> {
+        let patch = create(source, target);
+        assert_eq!(apply(&patch, source).unwrap(), target);
+        patch
+    }
+
+    #[test]
+    fn scattered_changes_round_trip() {
+        let source = source(0x1000);
+        let mut target = source.clone();
+        for offset in [0, 1, 9, 10, 0x100, 0x107, 0xfff] {
+            target[offset] ^= 0xff;
+        }
+        round_trip(&source, &target);
+    }
+
+    #[test]
+    fn moved_blocks_are_copied_from_the_source() {
+        let source = source(0x4000);
+        let mut target = source[..0x1000].to_vec();
+        target.extend_from_slice(&[0x42; 0x100]);
+        target.extend_from_slice(&source[0x1000..]);
+
+        let patch = round_trip(&source, &target);
+        assert!(patch.len() < 0x200);
+    }
+
+    #[test]
+    fn grown_and_shrunk_files_round_trip() {
+        let source = source(0x1000);
+        let mut grown = source.clone();
+        grown.extend_from_slice(&[0x42; 0x40]);
+        round_trip(&source, &grown);
+        round_trip(&source, &source[..0x800]);
+        round_trip(&[], &source);
+    }
+
+    #[test]
+    fn other_sources_are_rejected() {
+        let source = source(0x1000);
+        let mut target = source.clone();
+        target[0x10] = 0;
+        let patch = create(&source, &target);
+
+        let mut other = source.clone();
+        other[0x20] ^= 1;
+        assert!(matches!(
+            apply(&patch, &other),
+            Err(Error::Checksum { what: "source", .. })
+        ));
+    }
+
+    #[test]
+    fn corrupted_patches_are_rejected() {
+        let source = source(0x1000);
+        let mut patch = create(&source, &source[..0x800]);
+        patch[MAGIC.len() + 4] ^= 1;
+        assert!(matches!(
+            apply(&patch, &source),
+            Err(Error::Checksum { what: "patch", .. })
+        ));
+    }
+
+    #[test]
+    fn bogus_target_lengths_are_not_allocated() {
+        let source = source(0x100);
+        let mut actions = Vec::new();
+        write_action(&mut actions, SOURCE_READ, 0x10);
+        let patch = crafted(&source, usize::MAX >> 1, &actions);
+
+        assert!(matches!(
+            apply(&patch, &source),
+            Err(Error::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn copies_far_past_the_source_are_out_of_bounds() {
+        let source = source(0x100);
+        let mut actions = Vec::new();
+        write_action(&mut actions, SOURCE_COPY, 0x10);
+        write_signed(&mut actions, isize::MAX);
+        let patch = crafted(&source, usize::MAX >> 1, &actions);
+
+        assert!(matches!(
+            apply(&patch, &source),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn actions_past_the_target_length_are_out_of_bounds() {
+        let source = source(0x100);
+        let mut actions = Vec::new();
+        write_action(&mut actions, SOURCE_READ, 1);
+        write_action(&mut actions, TARGET_COPY, 0x1000);
+        write_signed(&mut actions, 0);
+        let patch = crafted(&source, 0x100, &actions);
+
+        assert!(matches!(
+            apply(&patch, &source),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+//! International Patching System: a list of `(offset, bytes)` records over the first 16 MiB.
+
+use super::{Error, Reader};
+
+pub const MAGIC: [u8; 5] = *b"PATCH";
+const FOOTER: [u8; 3] = *b"EOF";
+const MAX_LEN: usize = 1 << 24;
+const MAX_RECORD_LEN: usize = 0xffff;
+/// Unchanged bytes shorter than a record header are cheaper to rewrite than to skip.
+const RECORD_HEADER_LEN: usize = 5;
+/// A record at this offset would read as the footer.
+const FOOTER_OFFSET: usize = 0x45_4f46;
+
+pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>, Error> {
+    if target.len() > MAX_LEN {
+        return Err(Error::TooLarge { len: target.len() });
+    }
+
+    let mut patch = MAGIC.to_vec();
+    let differs = |offset: usize| source.get(offset) != Some(&target[offset]);
+    let mut offset = 0;
+    while offset < target.len() {
+        if !differs(offset) {
+            offset += 1;
+            continue;
+        }
+
+        let mut start = offset;
+        if start == FOOTER_OFFSET {
+            start -= 1;
+        }
+        let mut end = offset + 1;
+        let mut unchanged = 0;
+        while end < target.len() && end - start < MAX_RECORD_LEN && unchanged <= RECORD_HEADER_LEN {
+            unchanged = if differs(end) { 0 } else { unchanged + 1 };
+            end += 1;
+        }
+        end -= unchanged;
+
+        patch.extend_from_slice(&u32::try_from(start).unwrap().to_be_bytes()[1..]);
+        patch.extend_from_slice(&u16::try_from(end - start).unwrap().to_be_bytes());
+        patch.extend_from_slice(&target[start..end]);
+        offset = end;
+    }
+    patch.extend_from_slice(&FOOTER);
+    if target.len() < source.len() {
+        patch.extend_from_slice(&u32::try_from(target.len()).unwrap().to_be_bytes()[1..]);
+    }
+    Ok(patch)
+}
+
+pub fn apply(patch: &[u8], source: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut target = source.to_vec();
+    let mut reader = Reader {
+        patch,
+        offset: MAGIC.len(),
+    };
+    loop {
+        let header = reader.take(3)?;
+        if header == FOOTER {
+            break;
+        }
+        let offset = be(header);
+        let len = be(reader.take(2)?);
+        let (len, data) = if len == 0 {
+            let run_len = be(reader.take(2)?);
+            (run_len, None)
+        } else {
+            (len, Some(reader.take(len)?))
+        };
+        if target.len() < offset + len {
+            target.resize(offset + len, 0);
+        }
+        match data {
+            Some(data) => target[offset..offset + len].copy_from_slice(data),
+            None => target[offset..offset + len].fill(reader.take(1)?[0]),
+        }
+    }
+    if let Ok(truncate) = reader.take(3) {
+        target.truncate(be(truncate));
+    }
+    Ok(target)
+}
+
+fn be(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | usize::from(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(len: usize) -> Vec<u8> {
+        (0..len).map(|offset| (offset * 7 % 251) as u8).collect()
+    }
+
+    fn round_trip(source: &[u8], target: &[u8]) {
+        let patch = create(source, target).unwrap();
+        assert_eq!(apply(&patch, source).unwrap(), target);
+    }
+
+    #[test]
+    fn scattered_changes_round_trip() {
+        let source = source(0x1000);
+        let mut target = source.clone();
+        for offset in [0, 1, 9, 10, 0x100, 0x107, 0xfff] {
+            target[offset] ^= 0xff;
+        }
+        round_trip(&source, &target);
+    }
+
+    #[test]
+    fn changes_longer_than_a_record_round_trip() {
+        let source = source(0x2_0000);
+        let target: Vec<u8> = source.iter().map(|byte| !byte).collect();
+        round_trip(&source, &target);
+    }
+
+    #[test]
+    fn grown_and_shrunk_files_round_trip() {
+        let source = source(0x1000);
+        let mut grown = source.clone();
+        grown.extend_from_slice(&[0; 0x20]);
+        grown.extend_from_slice(&[0x42; 0x20]);
+        round_trip(&source, &grown);
+        round_trip(&source, &source[..0x800]);
+    }
+
+    #[test]
+    fn changes_at_the_footer_offset_round_trip() {
+        let source = vec![0; FOOTER_OFFSET + 0x10];
+        let mut target = source.clone();
+        target[FOOTER_OFFSET] = 1;
+
+        let patch = create(&source, &target).unwrap();
+        assert_ne!(patch[MAGIC.len()..MAGIC.len() + 3], FOOTER);
+        assert_eq!(apply(&patch, &source).unwrap(), target);
+    }
+
+    #[test]
+    fn files_past_16_mib_are_rejected() {
+        let target = vec![0; MAX_LEN + 1];
+        assert!(matches!(
+            create(&[], &target),
+            Err(Error::TooLarge { len }) if len == MAX_LEN + 1
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+//! Patches describing how to turn an original file into an edited one, so learnset changes can
+//! be shared without sharing the game data itself.
+
+use std::fmt::Display;
+
+pub mod bps;
+pub mod ips;
+
+/// Applies an IPS or BPS patch to `source`, telling the formats apart by their magic.
+pub fn apply(patch: &[u8], source: &[u8]) -> Result<Vec<u8>, Error> {
+    if patch.starts_with(&ips::MAGIC) {
+        ips::apply(patch, source)
+    } else if patch.starts_with(&bps::MAGIC) {
+        bps::apply(patch, source)
+    } else {
+        Err(Error::UnknownFormat)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownFormat,
+    /// The patch ends in the middle of a record or action.
+    Truncated {
+        offset: usize,
+    },
+    /// IPS only addresses the first 16 MiB of a file.
+    TooLarge {
+        len: usize,
+    },
+    SourceSize {
+        expected: usize,
+        found: usize,
+    },
+    Checksum {
+        what: &'static str,
+        expected: u32,
+        found: u32,
+    },
+    /// An action reads or writes outside of the source or target.
+    OutOfBounds {
+        offset: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::UnknownFormat => write!(f, "not an IPS or BPS patch"),
+            Self::Truncated { offset } => write!(f, "patch is truncated at offset {offset:#x}"),
+            Self::TooLarge { len } => write!(
+                f,
+                "IPS patches cannot address {len:#x} bytes, only the first 16 MiB"
+            ),
+            Self::SourceSize { expected, found } => write!(
+                f,
+                "patch expects a {expected} bytes file, this one is {found} bytes"
+            ),
+            Self::Checksum {
+                what,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{what} checksum mismatch, expected {expected:08x} but found {found:08x}"
+            ),
+            Self::OutOfBounds { offset } => {
+                write!(f, "patch action at offset {offset:#x} is out of bounds")
+            }
+        }
+    }
+}
+
+/// Cursor over the patch being applied.
+struct Reader<'a> {
+    patch: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .patch
+            .get(self.offset..self.offset + len)
+            .ok_or(Error::Truncated {
+                offset: self.offset,
+            })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+}
+
+/// CRC-32 (IEEE 802.3), the checksum used by BPS.
+#[must_use]
+pub fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut index = 0;
+        while index < 256 {
+            let mut crc = index as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 0 {
+                    crc >> 1
+                } else {
+                    (crc >> 1) ^ 0xedb8_8320
+                };
+                bit += 1;
+            }
+            table[index] = crc;
+            index += 1;
+        }
+        table
+    };
+    !data.iter().fold(!0, |crc, &byte| {
+        TABLE[usize::from((crc as u8) ^ byte)] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_the_ieee_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[test]
+    fn patches_are_told_apart_by_their_magic() {
+        let source = [1, 2, 3, 4];
+        let target = [1, 2, 5, 4, 6];
+        let ips = ips::create(&source, &target).unwrap();
+        let bps = bps::create(&source, &target);
+
+        assert_eq!(apply(&ips, &source).unwrap(), target);
+        assert_eq!(apply(&bps, &source).unwrap(), target);
+        assert!(matches!(apply(b"PAT", &source), Err(Error::UnknownFormat)));
+    }
+}
@@ -0,0 +1,594 @@
+//! Personal data archive (`a/0/1/6`): base stats, types, abilities and breeding data of each
+//! Pokémon, indexed like [`POKE_NAMES`](crate::names::POKE_NAMES).
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{
+    file::pokemon_name,
+    input::parse_in_range,
+    names::{
+        ABILITY_NAMES, EGG_GROUP_NAMES, GROWTH_RATE_NAMES, MACHINE_MOVES, SPECIAL_TUTOR_MOVES,
+        TUTOR_MOVES, TYPE_NAMES,
+    },
+    narc::{ArchiveError, Envelope},
+};
+
+/// Length of an entry in Black and White. Black 2 and White 2 entries are longer, with the
+/// tutor compatibility at the end.
+const MIN_ENTRY_LEN: usize = 0x3c;
+/// Highest item id of Black 2 and White 2.
+pub const MAX_ITEM: u16 = 638;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PersonalFile {
+    envelope: Envelope,
+    pub pokemons: Vec<Personal>,
+}
+
+impl PersonalFile {
+    #[must_use]
+    pub fn to_binary_format(&self) -> Vec<u8> {
+        let files: Vec<_> = self.pokemons.iter().map(|personal| &*personal.0).collect();
+        self.envelope.to_binary_format(&files)
+    }
+}
+
+impl TryFrom<&[u8]> for PersonalFile {
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (envelope, members) = Envelope::split(value).map_err(ParseError::Archive)?;
+        let pokemons = members
+            .iter()
+            .enumerate()
+            .map(|(pokemon, member)| {
+                if member.data.len() < MIN_ENTRY_LEN {
+                    return Err(ParseError::ShortEntry {
+                        pokemon,
+                        offset: member.offset,
+                        len: member.data.len(),
+                    });
+                }
+                Ok(Personal(Box::from(member.data)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { envelope, pokemons })
+    }
+}
+
+/// Reasons a personal data archive cannot be read.
+#[derive(Debug)]
+pub enum ParseError {
+    Archive(ArchiveError),
+    ShortEntry {
+        pokemon: usize,
+        offset: usize,
+        len: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::ShortEntry {
+                pokemon,
+                offset,
+                len,
+            } => write!(
+                f,
+                "personal data of {} at offset {offset:#x} is {len} bytes long, \
+                 {MIN_ENTRY_LEN} were expected",
+                pokemon_name(*pokemon)
+            ),
+        }
+    }
+}
+
+/// Personal data of one Pokémon, kept as read so bytes no field covers are written back as is.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Personal(Box<[u8]>);
+
+impl Personal {
+    #[must_use]
+    pub fn get(&self, field: Field) -> u16 {
+        let (offset, shift, bits) = field.location();
+        let raw = if shift + bits > 8 {
+            u16::from_le_bytes([self.0[offset], self.0[offset + 1]])
+        } else {
+            u16::from(self.0[offset])
+        };
+        (raw >> shift) & mask(bits)
+    }
+
+    /// Sets a field to a value within [`Field::range`], leaving the other bits untouched.
+    pub fn set(&mut self, field: Field, value: u16) {
+        debug_assert!(field.range().contains(&value));
+        let (offset, shift, bits) = field.location();
+        let mask = mask(bits) << shift;
+        if shift + bits > 8 {
+            let raw = u16::from_le_bytes([self.0[offset], self.0[offset + 1]]);
+            let raw = (raw & !mask) | ((value << shift) & mask);
+            self.0[offset..offset + 2].copy_from_slice(&raw.to_le_bytes());
+        } else {
+            let raw = u16::from(self.0[offset]);
+            self.0[offset] = ((raw & !mask) | ((value << shift) & mask)) as u8;
+        }
+    }
+
+    /// Groups of machine and tutor moves the entry has compatibility bits for, the Black 2 and
+    /// White 2 tutors missing from Black and White entries.
+    pub fn compatibilities(&self) -> impl Iterator<Item = Compatibility> + use<> {
+        let len = self.0.len();
+        Compatibility::ALL
+            .into_iter()
+            .filter(move |group| group.offset() + group.moves().len().div_ceil(8) <= len)
+    }
+
+    /// Whether the Pokémon can be taught the move at `index` in [`Compatibility::moves`].
+    #[must_use]
+    pub fn can_learn(&self, group: Compatibility, index: usize) -> bool {
+        let (offset, bit) = group.bit(index);
+        self.0[offset] & bit != 0
+    }
+
+    /// Sets a single compatibility bit, leaving the other bits untouched.
+    pub fn set_can_learn(&mut self, group: Compatibility, index: usize, learnable: bool) {
+        let (offset, bit) = group.bit(index);
+        if learnable {
+            self.0[offset] |= bit;
+        } else {
+            self.0[offset] &= !bit;
+        }
+    }
+}
+
+const fn mask(bits: u8) -> u16 {
+    u16::MAX >> (16 - bits)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Hp,
+    Attack,
+    Defense,
+    Speed,
+    SpecialAttack,
+    SpecialDefense,
+    Type1,
+    Type2,
+    CatchRate,
+    EvHp,
+    EvAttack,
+    EvDefense,
+    EvSpeed,
+    EvSpecialAttack,
+    EvSpecialDefense,
+    Item1,
+    Item2,
+    Item3,
+    GenderRatio,
+    EggCycles,
+    BaseHappiness,
+    GrowthRate,
+    EggGroup1,
+    EggGroup2,
+    Ability1,
+    Ability2,
+    HiddenAbility,
+}
+
+impl Field {
+    pub const ALL: [Self; 27] = [
+        Self::Hp,
+        Self::Attack,
+        Self::Defense,
+        Self::Speed,
+        Self::SpecialAttack,
+        Self::SpecialDefense,
+        Self::Type1,
+        Self::Type2,
+        Self::CatchRate,
+        Self::EvHp,
+        Self::EvAttack,
+        Self::EvDefense,
+        Self::EvSpeed,
+        Self::EvSpecialAttack,
+        Self::EvSpecialDefense,
+        Self::Item1,
+        Self::Item2,
+        Self::Item3,
+        Self::GenderRatio,
+        Self::EggCycles,
+        Self::BaseHappiness,
+        Self::GrowthRate,
+        Self::EggGroup1,
+        Self::EggGroup2,
+        Self::Ability1,
+        Self::Ability2,
+        Self::HiddenAbility,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Hp => "HP",
+            Self::Attack => "Attack",
+            Self::Defense => "Defense",
+            Self::Speed => "Speed",
+            Self::SpecialAttack => "Sp. Attack",
+            Self::SpecialDefense => "Sp. Defense",
+            Self::Type1 => "Type 1",
+            Self::Type2 => "Type 2",
+            Self::CatchRate => "Catch rate",
+            Self::EvHp => "HP EVs",
+            Self::EvAttack => "Attack EVs",
+            Self::EvDefense => "Defense EVs",
+            Self::EvSpeed => "Speed EVs",
+            Self::EvSpecialAttack => "Sp. Attack EVs",
+            Self::EvSpecialDefense => "Sp. Defense EVs",
+            Self::Item1 => "Held item (50%)",
+            Self::Item2 => "Held item (5%)",
+            Self::Item3 => "Held item (1%)",
+            Self::GenderRatio => "Gender ratio",
+            Self::EggCycles => "Egg cycles",
+            Self::BaseHappiness => "Base happiness",
+            Self::GrowthRate => "Growth rate",
+            Self::EggGroup1 => "Egg group 1",
+            Self::EggGroup2 => "Egg group 2",
+            Self::Ability1 => "Ability 1",
+            Self::Ability2 => "Ability 2",
+            Self::HiddenAbility => "Hidden ability",
+        }
+    }
+
+    /// Byte offset of the field in an entry, then the position and number of its bits there.
+    const fn location(self) -> (usize, u8, u8) {
+        match self {
+            Self::Hp => (0x00, 0, 8),
+            Self::Attack => (0x01, 0, 8),
+            Self::Defense => (0x02, 0, 8),
+            Self::Speed => (0x03, 0, 8),
+            Self::SpecialAttack => (0x04, 0, 8),
+            Self::SpecialDefense => (0x05, 0, 8),
+            Self::Type1 => (0x06, 0, 8),
+            Self::Type2 => (0x07, 0, 8),
+            Self::CatchRate => (0x08, 0, 8),
+            Self::EvHp => (0x0a, 0, 2),
+            Self::EvAttack => (0x0a, 2, 2),
+            Self::EvDefense => (0x0a, 4, 2),
+            Self::EvSpeed => (0x0a, 6, 2),
+            Self::EvSpecialAttack => (0x0a, 8, 2),
+            Self::EvSpecialDefense => (0x0a, 10, 2),
+            Self::Item1 => (0x0c, 0, 16),
+            Self::Item2 => (0x0e, 0, 16),
+            Self::Item3 => (0x10, 0, 16),
+            Self::GenderRatio => (0x12, 0, 8),
+            Self::EggCycles => (0x13, 0, 8),
+            Self::BaseHappiness => (0x14, 0, 8),
+            Self::GrowthRate => (0x15, 0, 8),
+            Self::EggGroup1 => (0x16, 0, 8),
+            Self::EggGroup2 => (0x17, 0, 8),
+            Self::Ability1 => (0x18, 0, 8),
+            Self::Ability2 => (0x19, 0, 8),
+            Self::HiddenAbility => (0x1a, 0, 8),
+        }
+    }
+
+    /// Values the games handle for this field.
+    #[must_use]
+    pub const fn range(self) -> RangeInclusive<u16> {
+        match self {
+            Self::Hp
+            | Self::Attack
+            | Self::Defense
+            | Self::Speed
+            | Self::SpecialAttack
+            | Self::SpecialDefense => 1..=255,
+            Self::Type1 | Self::Type2 => 0..=TYPE_NAMES.len() as u16 - 1,
+            Self::CatchRate | Self::GenderRatio | Self::EggCycles | Self::BaseHappiness => 0..=255,
+            Self::EvHp
+            | Self::EvAttack
+            | Self::EvDefense
+            | Self::EvSpeed
+            | Self::EvSpecialAttack
+            | Self::EvSpecialDefense => 0..=3,
+            Self::Item1 | Self::Item2 | Self::Item3 => 0..=MAX_ITEM,
+            Self::GrowthRate => 0..=GROWTH_RATE_NAMES.len() as u16 - 1,
+            Self::EggGroup1 | Self::EggGroup2 => 1..=EGG_GROUP_NAMES.len() as u16,
+            Self::Ability1 | Self::Ability2 | Self::HiddenAbility => 0..=ABILITY_NAMES.len() as u16,
+        }
+    }
+
+    /// Parses a value typed by the user, checking it against [`Field::range`].
+    pub fn parse(self, input: &str) -> Result<u16, String> {
+        parse_in_range(self.name(), &self.range(), input)
+    }
+
+    /// What a value of this field means, for the fields holding an id or a ratio.
+    #[must_use]
+    pub fn describe(self, value: u16) -> String {
+        let name = |names: &[&str], index: Option<u16>| {
+            index
+                .and_then(|index| names.get(usize::from(index)))
+                .map_or_else(|| String::from("?"), ToString::to_string)
+        };
+        match self {
+            Self::Type1 | Self::Type2 => name(&TYPE_NAMES, Some(value)),
+            Self::GrowthRate => name(&GROWTH_RATE_NAMES, Some(value)),
+            Self::EggGroup1 | Self::EggGroup2 => name(&EGG_GROUP_NAMES, value.checked_sub(1)),
+            Self::Ability1 | Self::Ability2 | Self::HiddenAbility if value == 0 => {
+                String::from("None")
+            }
+            Self::Ability1 | Self::Ability2 | Self::HiddenAbility => {
+                name(&ABILITY_NAMES, Some(value - 1))
+            }
+            Self::Item1 | Self::Item2 | Self::Item3 if value == 0 => String::from("None"),
+            Self::GenderRatio => match value {
+                0 => String::from("Always male"),
+                254 => String::from("Always female"),
+                255 => String::from("Genderless"),
+                ratio => format!("{:.1}% female", f64::from(ratio) * 100.0 / 254.0),
+            },
+            _ => String::new(),
+        }
+    }
+}
+
+/// Moves taught by machines or by a tutor, each Pokémon having one compatibility bit per move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// TMs then HMs.
+    Machines,
+    SpecialTutors,
+    DriftveilTutor,
+    LentimasTutor,
+    HumilauTutor,
+    NacreneTutor,
+}
+
+impl Compatibility {
+    pub const ALL: [Self; 6] = [
+        Self::Machines,
+        Self::SpecialTutors,
+        Self::DriftveilTutor,
+        Self::LentimasTutor,
+        Self::HumilauTutor,
+        Self::NacreneTutor,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Machines => "TMs and HMs",
+            Self::SpecialTutors => "Special tutors",
+            Self::DriftveilTutor => "Driftveil tutor",
+            Self::LentimasTutor => "Lentimas tutor",
+            Self::HumilauTutor => "Humilau tutor",
+            Self::NacreneTutor => "Nacrene tutor",
+        }
+    }
+
+    /// Ids of the moves, in the order of their bits.
+    #[must_use]
+    pub const fn moves(self) -> &'static [u16] {
+        match self {
+            Self::Machines => &MACHINE_MOVES,
+            Self::SpecialTutors => &SPECIAL_TUTOR_MOVES,
+            Self::DriftveilTutor => TUTOR_MOVES[0],
+            Self::LentimasTutor => TUTOR_MOVES[1],
+            Self::HumilauTutor => TUTOR_MOVES[2],
+            Self::NacreneTutor => TUTOR_MOVES[3],
+        }
+    }
+
+    /// Machine teaching the move at `index`, such as `TM01` or `HM06`, for the machines.
+    #[must_use]
+    pub fn machine(self, index: usize) -> Option<String> {
+        const TM_COUNT: usize = 95;
+        match self {
+            Self::Machines if index < TM_COUNT => Some(format!("TM{:02}", index + 1)),
+            Self::Machines => Some(format!("HM{:02}", index - TM_COUNT + 1)),
+            _ => None,
+        }
+    }
+
+    /// Byte offset of the first bit in an entry.
+    const fn offset(self) -> usize {
+        match self {
+            Self::Machines => 0x28,
+            Self::SpecialTutors => 0x38,
+            Self::DriftveilTutor => 0x3c,
+            Self::LentimasTutor => 0x40,
+            Self::HumilauTutor => 0x44,
+            Self::NacreneTutor => 0x48,
+        }
+    }
+
+    /// Byte offset and mask of the bit of the move at `index`.
+    const fn bit(self, index: usize) -> (usize, u8) {
+        (self.offset() + index / 8, 1 << (index % 8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entry of `len` bytes, each one set to `byte`.
+    fn entry(len: usize, byte: u8) -> Personal {
+        Personal(vec![byte; len].into_boxed_slice())
+    }
+
+    #[test]
+    fn fields_only_cover_their_own_bits() {
+        for field in Field::ALL {
+            let mut personal = entry(MIN_ENTRY_LEN, 0);
+            let max = *field.range().end();
+            personal.set(field, max);
+
+            assert_eq!(personal.get(field), max, "{field:?}");
+            for other in Field::ALL.into_iter().filter(|&other| other != field) {
+                assert_eq!(personal.get(other), 0, "{other:?} changed by {field:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fields_are_read_at_their_location() {
+        let mut personal = entry(MIN_ENTRY_LEN, 0);
+        personal.0[0x00] = 45;
+        personal.0[0x0a] = 0b1110_0100;
+        personal.0[0x0b] = 0b0000_1001;
+        personal.0[0x0e..0x10].copy_from_slice(&0x0123_u16.to_le_bytes());
+        personal.0[0x1a] = 34;
+
+        assert_eq!(personal.get(Field::Hp), 45);
+        assert_eq!(personal.get(Field::EvHp), 0);
+        assert_eq!(personal.get(Field::EvAttack), 1);
+        assert_eq!(personal.get(Field::EvDefense), 2);
+        assert_eq!(personal.get(Field::EvSpeed), 3);
+        assert_eq!(personal.get(Field::EvSpecialAttack), 1);
+        assert_eq!(personal.get(Field::EvSpecialDefense), 2);
+        assert_eq!(personal.get(Field::Item2), 0x0123);
+        assert_eq!(personal.get(Field::HiddenAbility), 34);
+    }
+
+    #[test]
+    fn setting_a_field_keeps_the_bits_around_it() {
+        let mut personal = entry(MIN_ENTRY_LEN, 0xff);
+        personal.set(Field::EvSpecialAttack, 0);
+        assert_eq!(personal.0[0x0a..0x0c], [0xff, 0b1111_1100]);
+
+        personal.set(Field::Item1, 0x0201);
+        assert_eq!(personal.0[0x0b..0x0f], [0b1111_1100, 0x01, 0x02, 0xff]);
+    }
+
+    #[test]
+    fn typed_values_are_checked_against_the_range_of_the_field() {
+        assert_eq!(Field::Hp.parse("255"), Ok(255));
+        assert!(Field::Hp.parse("0").is_err());
+        assert!(Field::Hp.parse("256").is_err());
+        assert!(Field::EvSpeed.parse("4").is_err());
+        assert!(Field::EggGroup1.parse("0").is_err());
+        assert_eq!(Field::Item3.parse(&MAX_ITEM.to_string()), Ok(MAX_ITEM));
+        assert_eq!(
+            Field::Item3.parse(&(MAX_ITEM + 1).to_string()),
+            Err(format!("Held item (1%) must be between 0 and {MAX_ITEM}"))
+        );
+    }
+
+    #[test]
+    fn entries_are_written_back_with_their_trailing_bytes() {
+        let entries: Vec<Vec<u8>> = vec![
+            (0..MIN_ENTRY_LEN as u8).collect(),
+            (0..0x4c).map(|byte: u8| byte.wrapping_mul(7)).collect(),
+        ];
+        let data = Envelope::unnamed(&[0; MIN_ENTRY_LEN]).to_binary_format(&entries);
+        let personal = PersonalFile::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(personal.pokemons[1].0.len(), 0x4c);
+        assert_eq!(personal.to_binary_format(), data);
+    }
+
+    /// Sets every field and compatibility bit of `personal` to the value read from it.
+    fn reencode(personal: &mut Personal) {
+        for field in Field::ALL {
+            personal.set(field, personal.get(field));
+        }
+        for group in personal.compatibilities() {
+            for index in 0..group.moves().len() {
+                personal.set_can_learn(group, index, personal.can_learn(group, index));
+            }
+        }
+    }
+
+    /// Entry of Bulbasaur, `len` bytes long, the bytes past the known fields holding a pattern.
+    fn bulbasaur(len: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..len)
+            .map(|index| (index as u8).wrapping_mul(37))
+            .collect();
+        data[..0x1b].copy_from_slice(&[
+            45, 49, 49, 45, 65, 65, 12, 3, 45, 1, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 31, 20, 70, 3, 1,
+            7, 65, 0, 34,
+        ]);
+        data
+    }
+
+    #[test]
+    fn black_and_white_entries_are_reencoded_unchanged() {
+        let data = bulbasaur(MIN_ENTRY_LEN);
+        let mut personal = Personal(Box::from(data.as_slice()));
+        reencode(&mut personal);
+
+        assert_eq!(
+            personal.compatibilities().collect::<Vec<_>>(),
+            [Compatibility::Machines, Compatibility::SpecialTutors]
+        );
+        assert_eq!(*personal.0, *data);
+    }
+
+    #[test]
+    fn black_2_and_white_2_entries_are_reencoded_unchanged() {
+        let data = bulbasaur(0x4c);
+        let mut personal = Personal(Box::from(data.as_slice()));
+        reencode(&mut personal);
+
+        assert_eq!(
+            personal.compatibilities().collect::<Vec<_>>(),
+            Compatibility::ALL
+        );
+        assert_eq!(*personal.0, *data);
+    }
+
+    #[test]
+    fn compatibility_bits_are_set_one_at_a_time() {
+        for group in Compatibility::ALL {
+            for index in 0..group.moves().len() {
+                let mut personal = entry(0x4c, 0);
+                personal.set_can_learn(group, index, true);
+                let set: Vec<_> = (0..personal.0.len() * 8)
+                    .filter(|bit| personal.0[bit / 8] & (1 << (bit % 8)) != 0)
+                    .collect();
+                assert_eq!(set, [group.offset() * 8 + index], "{group:?} {index}");
+                assert!(personal.can_learn(group, index));
+
+                let mut personal = entry(0x4c, 0xff);
+                personal.set_can_learn(group, index, false);
+                let cleared = personal
+                    .0
+                    .iter()
+                    .map(|byte| byte.count_zeros())
+                    .sum::<u32>();
+                assert_eq!(cleared, 1, "{group:?} {index}");
+                assert!(!personal.can_learn(group, index));
+            }
+        }
+    }
+
+    #[test]
+    fn machines_start_at_0x28_and_tutors_at_0x38() {
+        let mut personal = entry(0x4c, 0);
+        personal.set_can_learn(Compatibility::Machines, 9, true);
+        personal.set_can_learn(Compatibility::SpecialTutors, 0, true);
+        personal.set_can_learn(Compatibility::NacreneTutor, 2, true);
+
+        assert_eq!(personal.0[0x29], 0b10);
+        assert_eq!(personal.0[0x38], 0b1);
+        assert_eq!(personal.0[0x48], 0b100);
+    }
+
+    #[test]
+    fn short_entries_are_reported_at_their_offset() {
+        let entries = [vec![0; MIN_ENTRY_LEN], vec![0; MIN_ENTRY_LEN - 1]];
+        let data = Envelope::unnamed(&[]).to_binary_format(&entries);
+        let (_, members) = Envelope::split(&data).unwrap();
+
+        assert!(matches!(
+            PersonalFile::try_from(data.as_slice()),
+            Err(ParseError::ShortEntry { pokemon: 1, offset, len: 0x3b })
+                if offset == members[1].offset
+        ));
+    }
+}
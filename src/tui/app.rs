@@ -1,90 +1,1288 @@
+use std::ops::{Range, RangeInclusive};
+
+use super::{
+    history::{Change, Edit, History},
+    learners::{self, Learners},
+    move_editor::{self, MoveEditor},
+    move_picker::{self, MovePicker, Purpose},
+    move_screen::{self, MoveScreen},
+    popup_area,
+    problems::{self, Problems},
+};
 use crate::{
-    file::{Move, SaveFile},
-    names::POKE_NAMES,
+    archives::Archives,
+    check::{self, Severity},
+    diff::{self, Status},
+    evolution::{EVOLUTION_COUNT, Evolution, Parameter, Part},
+    file::{Move, SaveFile, level_order, pokemon_name},
+    move_data::{self, MoveData},
+    names::{POKE_NAMES, move_id_to_name},
+    personal::{Compatibility, Field, Personal},
 };
 use ratatui::{
+    Frame,
+    crossterm::event::KeyCode,
     layout::{Constraint, Direction, Layout},
     style::{Style, Stylize},
-    widgets::{Block, HighlightSpacing, Row, Table, TableState},
-    Frame,
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, HighlightSpacing, Paragraph, Row, Table, TableState},
 };
 
 pub struct App {
     pub save_file: SaveFile,
+    pub archives: Archives,
     gui_state: State,
+    tab: Tab,
+    move_picker: Option<MovePicker>,
+    move_editor: Option<MoveEditor>,
+    move_screen: Option<MoveScreen>,
+    learners: Option<Learners>,
+    problems: Option<Problems>,
+    history: History<State>,
+    /// Learnsets as they were when the file was loaded or last saved.
+    saved_pokemons: Vec<Vec<Move>>,
+    saved_archives: Archives,
+    /// Whether the learnsets or the other archives differ from the saved ones, updated by
+    /// [`App::refresh`].
+    modified: bool,
+    /// Number and highest severity of the problems of the learnsets, updated by
+    /// [`App::refresh`] rather than checked on every frame.
+    problem_count: Option<(usize, Severity)>,
+    quit_prompt: bool,
+    status: Option<Span<'static>>,
+    reference: Option<Reference>,
+    compare: bool,
+    /// Name or national number prefix the Pokémon list is filtered by.
+    filter: String,
+    prompt: Option<Prompt>,
+    /// Moves copied by [`App::yank`].
+    clipboard: Vec<Move>,
+    /// Pokémon and index of the move where the marked range starts.
+    mark: Option<(usize, usize)>,
+    /// Whether learnsets are sorted by level after every edit.
+    auto_sort: bool,
+}
+
+/// Rows moved at once by PageUp and PageDown.
+const PAGE_LEN: usize = 20;
+/// Move data shown next to each move of the learnset, when it is loaded.
+const MOVE_COLUMNS: [move_data::Field; 5] = [
+    move_data::Field::Type,
+    move_data::Field::Category,
+    move_data::Field::Power,
+    move_data::Field::Accuracy,
+    move_data::Field::Pp,
+];
+/// Machine and tutor moves shown on each row of the compatibility grid.
+const GRID_COLUMNS: usize = 3;
+
+/// Input typed in the status line.
+enum Prompt {
+    /// Editing [`App::filter`], which applies as it is typed.
+    Search,
+    /// National number of the Pokémon to go to.
+    GoTo(String),
+    /// New value of a personal data field of the current Pokémon.
+    Field(Field, String),
+    /// New value of a part of one of the current Pokémon's evolutions.
+    Evolution(usize, Part, String),
+}
+
+/// What the right pane shows about the current Pokémon.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Learnset,
+    Personal,
+    Teachable,
+    Evolutions,
+}
+
+/// Row of the compatibility grid.
+enum GridRow {
+    /// Name of a group of moves, before its moves.
+    Group(Compatibility),
+    /// Indices of moves in [`App::teachables`].
+    Moves(Range<usize>),
+}
+
+/// File shown next to the edited one in compare mode, such as the unedited game.
+struct Reference {
+    name: String,
+    save_file: SaveFile,
 }
 
+/// Learnsets of the current Pokémon in both files, with the status of each move.
+struct Comparison<'a> {
+    name: &'a str,
+    moves: &'a [Move],
+    reference: Vec<Status>,
+    edited: Vec<Status>,
+}
+
+#[derive(Clone, Copy)]
 struct State {
     current_pokemon: usize,
     selected: Selected,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Selected {
     Pokemon,
     Move(usize),
+    /// Index in [`Field::ALL`].
+    Field(usize),
+    /// Index in [`App::teachables`].
+    Teachable(usize),
+    /// Index in the egg moves of the current Pokémon.
+    EggMove(usize),
+    /// Index in the evolutions of the current Pokémon, and the part of it.
+    Evolution(usize, Part),
 }
 
 impl App {
-    pub fn from_save_file(save_file: SaveFile) -> Self {
-        debug_assert!(save_file.pokemons.len() == POKE_NAMES.len());
-        Self {
+    pub fn from_save_file(save_file: SaveFile, archives: Archives) -> Self {
+        let mut app = Self {
             gui_state: State {
                 current_pokemon: 0,
                 selected: Selected::Pokemon,
             },
+            tab: Tab::Learnset,
+            move_picker: None,
+            move_editor: None,
+            move_screen: None,
+            learners: None,
+            problems: None,
+            history: History::new(),
+            saved_pokemons: save_file.pokemons.clone(),
+            saved_archives: archives.clone(),
+            archives,
+            modified: false,
+            problem_count: None,
+            quit_prompt: false,
+            status: None,
+            reference: None,
+            compare: false,
+            filter: String::new(),
+            prompt: None,
+            clipboard: Vec::new(),
+            mark: None,
+            auto_sort: false,
             save_file,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Loads the file to compare with, and turns compare mode on.
+    pub fn set_reference(&mut self, name: String, save_file: SaveFile) {
+        self.reference = Some(Reference { name, save_file });
+        self.compare = true;
+    }
+
+    pub fn toggle_compare(&mut self) {
+        if self.reference.is_some() {
+            self.compare = !self.compare;
+        } else {
+            self.report_error("no file to compare with, open one with --compare");
         }
     }
 
     pub fn select_next(&mut self) {
-        self.gui_state.next(self.get_moves().len());
+        self.move_cursor(|position, len| (position + 1) % len);
     }
 
     pub fn select_previous(&mut self) {
-        self.gui_state.previous(self.get_moves().len());
+        self.move_cursor(|position, len| (position + len - 1) % len);
+    }
+
+    pub fn select_page_down(&mut self) {
+        self.move_cursor(|position, len| (position + PAGE_LEN).min(len - 1));
+    }
+
+    pub fn select_page_up(&mut self) {
+        self.move_cursor(|position, _| position.saturating_sub(PAGE_LEN));
+    }
+
+    pub fn select_first(&mut self) {
+        self.move_cursor(|_, _| 0);
+    }
+
+    pub fn select_last(&mut self) {
+        self.move_cursor(|_, len| len - 1);
+    }
+
+    /// Moves the selection of the focused table, `to` mapping the selected position and the
+    /// number of rows to the new position. Only Pokémon matching the filter can be selected.
+    fn move_cursor(&mut self, to: impl FnOnce(usize, usize) -> usize) {
+        match self.gui_state.selected {
+            Selected::Pokemon => {
+                let visible = self.visible_pokemons();
+                if !visible.is_empty() {
+                    let position = self.pokemon_position(&visible).unwrap_or_default();
+                    self.gui_state.current_pokemon = visible[to(position, visible.len())];
+                }
+            }
+            Selected::Move(index) => {
+                let len = self.get_moves().len();
+                if len > 0 {
+                    self.gui_state.selected = Selected::Move(to(index, len));
+                }
+            }
+            Selected::Field(index) => {
+                self.gui_state.selected = Selected::Field(to(index, Field::ALL.len()));
+            }
+            Selected::Evolution(index, part) => {
+                self.gui_state.selected = Selected::Evolution(to(index, EVOLUTION_COUNT), part);
+            }
+            Selected::EggMove(index) => {
+                let len = self.egg_moves().len();
+                if len > 0 {
+                    self.gui_state.selected = Selected::EggMove(to(index, len));
+                }
+            }
+            Selected::Teachable(index) => {
+                // Rows of the grid move the selection, which keeps its column when it can.
+                let rows: Vec<Range<usize>> = self
+                    .grid()
+                    .into_iter()
+                    .filter_map(|row| match row {
+                        GridRow::Moves(range) => Some(range),
+                        GridRow::Group(_) => None,
+                    })
+                    .collect();
+                if let Some(position) = rows.iter().position(|row| row.contains(&index)) {
+                    let column = index - rows[position].start;
+                    let row = &rows[to(position, rows.len())];
+                    self.gui_state.selected =
+                        Selected::Teachable((row.start + column).min(row.end - 1));
+                }
+            }
+        }
     }
     pub fn select_right(&mut self) {
-        self.gui_state.select_moves();
+        match (self.tab, self.gui_state.selected) {
+            (Tab::Learnset, Selected::Pokemon) if !self.get_moves().is_empty() => {
+                self.gui_state.select_moves();
+            }
+            (Tab::Learnset, Selected::Pokemon | Selected::Move(_))
+                if !self.egg_moves().is_empty() =>
+            {
+                self.gui_state.selected = Selected::EggMove(0);
+            }
+            (Tab::Personal, _) if self.has_personal() => {
+                self.gui_state.selected = Selected::Field(0)
+            }
+            (Tab::Teachable, Selected::Teachable(index)) => {
+                let next = index + 1;
+                if self.grid_row_of(index).contains(&next) {
+                    self.gui_state.selected = Selected::Teachable(next);
+                }
+            }
+            (Tab::Teachable, _) if self.has_personal() => {
+                self.gui_state.selected = Selected::Teachable(0);
+            }
+            (Tab::Evolutions, Selected::Evolution(index, part)) => {
+                if let Some(&next) = Part::ALL.iter().skip_while(|&&p| p != part).nth(1) {
+                    self.gui_state.selected = Selected::Evolution(index, next);
+                }
+            }
+            (Tab::Evolutions, _) if self.current_evolutions().is_some() => {
+                self.gui_state.selected = Selected::Evolution(0, Part::Method);
+            }
+            _ => {}
+        }
     }
-    pub fn select_left(&mut self) {
+
+    /// Switches the right pane to the next tab whose archive is loaded, after the learnset: the
+    /// personal data, the machine and tutor compatibility, and the evolutions.
+    pub fn toggle_tab(&mut self) {
+        let tabs = [
+            Tab::Learnset,
+            Tab::Personal,
+            Tab::Teachable,
+            Tab::Evolutions,
+        ];
+        let available = |tab: &Tab| match tab {
+            Tab::Learnset => true,
+            Tab::Personal | Tab::Teachable => self.archives.personal.is_some(),
+            Tab::Evolutions => self.archives.evolutions.is_some(),
+        };
+        let position = tabs
+            .iter()
+            .position(|&tab| tab == self.tab)
+            .unwrap_or_default();
+        let next = tabs[position + 1..]
+            .iter()
+            .chain(&tabs)
+            .find(|&tab| available(tab))
+            .copied()
+            .unwrap_or(Tab::Learnset);
+        if next == self.tab {
+            self.report_error("nothing else to show, open a ROM or use --personal or --evolutions");
+            return;
+        }
+        self.tab = next;
         self.gui_state.select_pokemon();
     }
 
+    /// Machine and tutor moves of the current Pokémon's personal data, group by group.
+    fn teachables(&self) -> Vec<(Compatibility, usize)> {
+        let Some(personal) = self.current_personal() else {
+            return Vec::new();
+        };
+        personal
+            .compatibilities()
+            .flat_map(|group| (0..group.moves().len()).map(move |index| (group, index)))
+            .collect()
+    }
+
+    /// Layout of [`App::teachables`], each group starting on a new row.
+    fn grid(&self) -> Vec<GridRow> {
+        let mut rows = Vec::new();
+        let mut start = 0;
+        let teachables = self.teachables();
+        for chunk in teachables.chunk_by(|(a, _), (b, _)| a == b) {
+            rows.push(GridRow::Group(chunk[0].0));
+            for moves in chunk.chunks(GRID_COLUMNS) {
+                rows.push(GridRow::Moves(start..start + moves.len()));
+                start += moves.len();
+            }
+        }
+        rows
+    }
+
+    /// Indices of the moves on the same row of the grid as the move at `index`.
+    fn grid_row_of(&self, index: usize) -> Range<usize> {
+        self.grid()
+            .into_iter()
+            .find_map(|row| match row {
+                GridRow::Moves(range) if range.contains(&index) => Some(range),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether the current Pokémon has personal data to show.
+    fn has_personal(&self) -> bool {
+        self.archives
+            .personal
+            .as_ref()
+            .is_some_and(|personal| self.gui_state.current_pokemon < personal.pokemons.len())
+    }
+
+    /// Shows the tab the selection belongs to, after restoring a state from the history or
+    /// jumping to a move.
+    const fn sync_tab(&mut self) {
+        match self.gui_state.selected {
+            Selected::Move(_) | Selected::EggMove(_) => self.tab = Tab::Learnset,
+            Selected::Field(_) => self.tab = Tab::Personal,
+            Selected::Teachable(_) => self.tab = Tab::Teachable,
+            Selected::Evolution(..) => self.tab = Tab::Evolutions,
+            Selected::Pokemon => {}
+        }
+    }
+    pub fn select_left(&mut self) {
+        match self.gui_state.selected {
+            Selected::Teachable(index) if self.grid_row_of(index).start < index => {
+                self.gui_state.selected = Selected::Teachable(index - 1);
+            }
+            Selected::EggMove(_) if !self.get_moves().is_empty() => self.gui_state.select_moves(),
+            Selected::Evolution(index, part) if part != Part::Method => {
+                let previous = Part::ALL.iter().take_while(|&&p| p != part).last();
+                self.gui_state.selected = Selected::Evolution(index, *previous.unwrap_or(&part));
+            }
+            _ => self.gui_state.select_pokemon(),
+        }
+    }
+
     pub fn delete_move_selected(&mut self) {
         match self.gui_state.selected {
-            Selected::Pokemon => todo!("Handle delete when no move is selected, AKA do nothing."),
+            Selected::Pokemon | Selected::Field(_) | Selected::Teachable(_) => {}
             Selected::Move(index) => {
-                self.get_moves_mut().remove(index);
-                self.gui_state.selected = match self.get_moves().len() {
-                    0 => Selected::Pokemon, // todo handle empty move set...
-                    n if index >= n => Selected::Move(n - 1),
-                    _ => Selected::Move(index),
+                let old = self.get_moves()[index];
+                self.apply(Edit::Delete { index, old });
+            }
+            Selected::EggMove(index) => {
+                let old = self.egg_moves().to_vec();
+                let mut new = old.clone();
+                new.remove(index);
+                self.apply(Edit::EggMoves { old, new });
+            }
+            Selected::Evolution(index, _) => self.set_evolution(index, Evolution::default()),
+        }
+    }
+
+    fn current_evolutions(&self) -> Option<&[Evolution; EVOLUTION_COUNT]> {
+        let evolutions = self.archives.evolutions.as_ref()?;
+        evolutions.pokemons.get(self.gui_state.current_pokemon)
+    }
+
+    fn evolution(&self, index: usize) -> Option<Evolution> {
+        self.current_evolutions()
+            .map(|evolutions| evolutions[index])
+    }
+
+    fn set_evolution(&mut self, index: usize, new: Evolution) {
+        if let Some(old) = self.evolution(index)
+            && old != new
+        {
+            self.apply(Edit::Evolution { index, old, new });
+        }
+    }
+
+    /// Egg moves of the current Pokémon, empty when there are none to show.
+    fn egg_moves(&self) -> &[u16] {
+        self.current_egg_moves().map_or(&[], Vec::as_slice)
+    }
+
+    fn current_egg_moves(&self) -> Option<&Vec<u16>> {
+        let egg_moves = self.archives.egg_moves.as_ref()?;
+        egg_moves.pokemons.get(self.gui_state.current_pokemon)
+    }
+
+    /// Adds a move at the end of the current Pokémon's egg moves, unless it is already there.
+    fn add_egg_move(&mut self, id: u16) {
+        let old = self.egg_moves().to_vec();
+        if old.contains(&id) {
+            self.report_error(format!(
+                "{} is already an egg move of {}",
+                move_id_to_name(id.into()),
+                pokemon_name(self.gui_state.current_pokemon)
+            ));
+            return;
+        }
+        let mut new = old.clone();
+        new.push(id);
+        self.apply(Edit::EggMoves { old, new });
+    }
+
+    pub const fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_pokemons.clone_from(&self.save_file.pokemons);
+        self.saved_archives.clone_from(&self.archives);
+        self.refresh();
+        self.status = Some(Span::from("Saved").green());
+    }
+
+    /// Updates what the status line shows about the learnsets, after they changed.
+    fn refresh(&mut self) {
+        self.modified =
+            self.save_file.pokemons != self.saved_pokemons || self.archives != self.saved_archives;
+        let problems = check::check(&self.save_file);
+        self.problem_count = problems
+            .iter()
+            .map(check::Problem::severity)
+            .max()
+            .map(|severity| (problems.len(), severity));
+    }
+
+    pub fn report_error(&mut self, error: impl std::fmt::Display) {
+        self.status = Some(Span::from(format!("Error: {error}")).red());
+    }
+
+    pub const fn open_quit_prompt(&mut self) {
+        self.quit_prompt = true;
+    }
+
+    pub const fn close_quit_prompt(&mut self) {
+        self.quit_prompt = false;
+    }
+
+    pub const fn is_quit_prompt_open(&self) -> bool {
+        self.quit_prompt
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(changes) = self.history.undo() {
+            for change in changes.iter().rev() {
+                change
+                    .edit
+                    .revert(change.pokemon, &mut self.save_file, &mut self.archives);
+                self.gui_state = change.before;
+            }
+            self.reveal_current_pokemon();
+            self.sync_tab();
+            self.refresh();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(changes) = self.history.redo() {
+            for change in changes {
+                change
+                    .edit
+                    .apply(change.pokemon, &mut self.save_file, &mut self.archives);
+                self.gui_state = change.after;
+            }
+            self.reveal_current_pokemon();
+            self.sync_tab();
+            self.refresh();
+        }
+    }
+
+    pub fn toggle_auto_sort(&mut self) {
+        self.auto_sort = !self.auto_sort;
+    }
+
+    /// Sorts the current learnset by level.
+    pub fn sort_learnset(&mut self) {
+        if !self.is_learnset_shown("sort it") {
+            return;
+        }
+        let changes = self.sort([self.gui_state.current_pokemon]);
+        self.status = Some(Span::from(if changes.is_empty() {
+            "Already sorted"
+        } else {
+            "Sorted by level"
+        }));
+        self.history.record(changes);
+        self.refresh();
+    }
+
+    /// Sorts every learnset of the file by level, as a single step of the history.
+    pub fn sort_all(&mut self) {
+        if !self.is_learnset_shown("sort the learnsets") {
+            return;
+        }
+        let changes = self.sort(0..self.save_file.pokemons.len());
+        self.status = Some(Span::from(format!("Sorted {} learnsets", changes.len())));
+        self.history.record(changes);
+        self.refresh();
+    }
+
+    /// Sorts the learnsets of `pokemons` with [`level_order`], the selection following the
+    /// selected move, and returns the changes to record.
+    fn sort(&mut self, pokemons: impl IntoIterator<Item = usize>) -> Vec<Change<State>> {
+        let before = self.gui_state;
+        self.mark = None;
+        let mut edits = Vec::new();
+        for pokemon in pokemons {
+            let old = &self.save_file.pokemons[pokemon];
+            let order = level_order(old);
+            if order.iter().copied().eq(0..old.len()) {
+                continue;
+            }
+            let new: Vec<Move> = order.iter().map(|&index| old[index]).collect();
+            if let (true, Selected::Move(selected)) = (
+                pokemon == self.gui_state.current_pokemon,
+                self.gui_state.selected,
+            ) {
+                let selected = order.iter().position(|&index| index == selected);
+                self.gui_state.selected = Selected::Move(selected.unwrap_or_default());
+            }
+            let old = std::mem::replace(&mut self.save_file.pokemons[pokemon], new.clone());
+            edits.push((pokemon, Edit::Set { old, new }));
+        }
+        edits
+            .into_iter()
+            .map(|(pokemon, edit)| Change {
+                pokemon,
+                edit,
+                before,
+                after: self.gui_state,
+            })
+            .collect()
+    }
+
+    /// Starts or cancels marking a range of moves, from the selected one to the cursor.
+    pub fn toggle_mark(&mut self) {
+        self.mark = match (self.mark_range(), self.gui_state.selected) {
+            (None, Selected::Move(index)) => Some((self.gui_state.current_pokemon, index)),
+            _ => None,
+        };
+    }
+
+    /// Marked moves, when the range was started in the current learnset and a move is selected.
+    fn mark_range(&self) -> Option<RangeInclusive<usize>> {
+        let (pokemon, start) = self.mark?;
+        let Selected::Move(end) = self.gui_state.selected else {
+            return None;
+        };
+        (pokemon == self.gui_state.current_pokemon && start < self.get_moves().len())
+            .then(|| start.min(end)..=start.max(end))
+    }
+
+    /// Copies the marked moves, the selected move, or the whole learnset when no move is selected.
+    pub fn yank(&mut self) {
+        let moves = self.get_moves();
+        let name = pokemon_name(self.gui_state.current_pokemon);
+        let (clipboard, message) = match (self.mark_range(), self.gui_state.selected) {
+            (Some(range), _) => (
+                moves[range.clone()].to_vec(),
+                format!("Copied {} moves of {name}", range.count()),
+            ),
+            (None, Selected::Move(index)) => (
+                vec![moves[index]],
+                format!("Copied {} of {name}", moves[index].name()),
+            ),
+            (
+                None,
+                Selected::Pokemon
+                | Selected::Field(_)
+                | Selected::Teachable(_)
+                | Selected::EggMove(_)
+                | Selected::Evolution(..),
+            ) => (moves.to_vec(), format!("Copied the learnset of {name}")),
+        };
+        self.clipboard = clipboard;
+        self.mark = None;
+        self.status = Some(Span::from(message));
+    }
+
+    /// Replaces the current learnset with the copied moves.
+    pub fn paste_replace(&mut self) {
+        if !self.can_paste() {
+            return;
+        }
+        let new = self.clipboard.clone();
+        self.set_learnset(new);
+    }
+
+    /// Adds the copied moves to the current learnset, ordered by level and skipping the ones it
+    /// already has at the same level.
+    pub fn paste_merge(&mut self) {
+        if !self.can_paste() {
+            return;
+        }
+        let mut new = self.get_moves().to_vec();
+        for &pasted in &self.clipboard {
+            if !new.contains(&pasted) {
+                let index = new.partition_point(|m| m.level <= pasted.level);
+                new.insert(index, pasted);
+            }
+        }
+        self.set_learnset(new);
+    }
+
+    /// Whether there are moves to paste and the learnset they go to is shown.
+    fn can_paste(&mut self) -> bool {
+        if !self.is_learnset_shown("paste moves") {
+            return false;
+        }
+        if self.clipboard.is_empty() {
+            self.report_error("nothing copied, press y to copy moves");
+        }
+        !self.clipboard.is_empty()
+    }
+
+    /// Whether the learnset is shown, reporting that switching to it is needed to do `action`
+    /// otherwise, so hidden learnsets are never edited.
+    fn is_learnset_shown(&mut self, action: &str) -> bool {
+        if self.tab != Tab::Learnset {
+            self.report_error(format!("switch to the learnset with Tab to {action}"));
+        }
+        self.tab == Tab::Learnset
+    }
+
+    fn set_learnset(&mut self, new: Vec<Move>) {
+        let old = self.get_moves().to_vec();
+        if old != new {
+            self.apply(Edit::Set { old, new });
+        }
+    }
+
+    pub fn open_search(&mut self) {
+        self.prompt = Some(Prompt::Search);
+        self.gui_state.select_pokemon();
+    }
+
+    /// Starts typing the national number of a Pokémon to go to, beginning with `digit`.
+    pub fn open_go_to(&mut self, digit: char) {
+        self.prompt = Some(Prompt::GoTo(digit.to_string()));
+    }
+
+    pub const fn is_filtered(&self) -> bool {
+        !self.filter.is_empty()
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+    }
+
+    fn matches_filter(&self, pokemon: usize) -> bool {
+        let query = self.filter.trim().to_lowercase();
+        query.is_empty()
+            || (pokemon + 1).to_string().starts_with(&query)
+            || POKE_NAMES
+                .get(pokemon)
+                .is_some_and(|name| name.to_lowercase().contains(&query))
+    }
+
+    /// Indices of the Pokémon shown in the list.
+    fn visible_pokemons(&self) -> Vec<usize> {
+        (0..self.save_file.pokemons.len())
+            .filter(|&pokemon| self.matches_filter(pokemon))
+            .collect()
+    }
+
+    /// Position of the current Pokémon among the `visible` ones.
+    fn pokemon_position(&self, visible: &[usize]) -> Option<usize> {
+        visible
+            .iter()
+            .position(|&pokemon| pokemon == self.gui_state.current_pokemon)
+    }
+
+    /// Clears the filter when it hides the current Pokémon, after jumping to it.
+    fn reveal_current_pokemon(&mut self) {
+        if !self.matches_filter(self.gui_state.current_pokemon) {
+            self.filter.clear();
+        }
+    }
+
+    fn search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.prompt = None;
+            }
+            KeyCode::Enter => self.prompt = None,
+            KeyCode::Backspace => _ = self.filter.pop(),
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => {}
+        }
+        if let Some(&first) = self.visible_pokemons().first()
+            && !self.matches_filter(self.gui_state.current_pokemon)
+        {
+            self.gui_state.current_pokemon = first;
+        }
+    }
+
+    fn go_to_key(&mut self, code: KeyCode) {
+        let Some(Prompt::GoTo(number)) = &mut self.prompt else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => {
+                number.pop();
+                if number.is_empty() {
+                    self.prompt = None;
+                }
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() && number.len() < 3 => number.push(c),
+            KeyCode::Enter => {
+                let number = number.parse().unwrap_or_default();
+                self.prompt = None;
+                if (1..=self.save_file.pokemons.len()).contains(&number) {
+                    self.gui_state = State {
+                        current_pokemon: number - 1,
+                        selected: Selected::Pokemon,
+                    };
+                    self.reveal_current_pokemon();
+                } else {
+                    self.report_error(format!("there is no Pokémon #{number}"));
                 }
             }
+            _ => {}
         }
     }
 
+    /// Applies an edit to the current Pokémon and records it so it can be undone.
+    fn apply(&mut self, edit: Edit) {
+        let before = self.gui_state;
+        self.status = None;
+        edit.apply(
+            before.current_pokemon,
+            &mut self.save_file,
+            &mut self.archives,
+        );
+        self.mark = None;
+        self.gui_state.selected = match edit {
+            Edit::Insert { index, .. } | Edit::Replace { index, .. } => Selected::Move(index),
+            Edit::Delete { index, .. } => match self.get_moves().len() {
+                0 => Selected::Pokemon, // todo handle empty move set...
+                n if index >= n => Selected::Move(n - 1),
+                _ => Selected::Move(index),
+            },
+            Edit::Personal { .. }
+            | Edit::Compatibility { .. }
+            | Edit::Evolution { .. }
+            | Edit::MoveData { .. }
+            | Edit::MoveFlag { .. } => self.gui_state.selected,
+            Edit::EggMoves { ref old, ref new } => match (self.gui_state.selected, new.len()) {
+                (_, n) if n > old.len() => Selected::EggMove(n - 1),
+                (Selected::EggMove(_), 0) => Selected::Pokemon,
+                (Selected::EggMove(index), n) if index >= n => Selected::EggMove(n - 1),
+                (selected, _) => selected,
+            },
+            Edit::Set { .. } => match (self.gui_state.selected, self.get_moves().len()) {
+                (Selected::Move(_), 0) => Selected::Pokemon,
+                (Selected::Move(index), n) if index >= n => Selected::Move(n - 1),
+                (selected, _) => selected,
+            },
+        };
+        let mut changes = vec![Change {
+            pokemon: before.current_pokemon,
+            edit,
+            before,
+            after: self.gui_state,
+        }];
+        if self.auto_sort {
+            changes.extend(self.sort([before.current_pokemon]));
+        }
+        self.history.record(changes);
+        self.refresh();
+    }
+
+    /// Opens the picker adding a move to the learnset, or to the egg moves when one is selected.
+    pub fn open_move_picker(&mut self) {
+        if let Selected::EggMove(_) = self.gui_state.selected {
+            self.open_egg_move_picker();
+        } else if self.is_learnset_shown("add moves") {
+            self.move_picker = Some(MovePicker::new());
+        }
+    }
+
+    pub fn open_egg_move_picker(&mut self) {
+        if self.current_egg_moves().is_none() {
+            self.report_error("no egg moves for this Pokémon, open a ROM or use --egg-moves");
+        } else {
+            self.move_picker = Some(MovePicker::egg_move());
+        }
+    }
+
+    pub fn open_move_lookup(&mut self) {
+        self.move_picker = Some(MovePicker::lookup());
+    }
+
+    /// Shows the data of the selected move, or asks which move to show when none is selected.
+    pub fn open_move_data(&mut self) {
+        if self.archives.move_data.is_none() {
+            self.report_error("no move data, open a ROM or use --move-data");
+            return;
+        }
+        let id = match self.gui_state.selected {
+            Selected::Move(index) => self.get_moves()[index].id,
+            Selected::EggMove(index) => self.egg_moves()[index],
+            Selected::Evolution(index, _) => match self.evolution(index) {
+                Some(evolution) if evolution.parameter_kind() == Parameter::Move => {
+                    evolution.parameter
+                }
+                _ => 0,
+            },
+            Selected::Pokemon | Selected::Field(_) | Selected::Teachable(_) => 0,
+        };
+        match id {
+            0 => self.move_picker = Some(MovePicker::move_data()),
+            id => self.open_move_screen(id),
+        }
+    }
+
+    fn open_move_screen(&mut self, id: u16) {
+        if self.move_data(id).is_some() {
+            self.move_screen = Some(MoveScreen::new(id));
+        } else {
+            self.report_error(format!("no data for move #{id} in the move data archive"));
+        }
+    }
+
+    fn move_data(&self, id: u16) -> Option<&MoveData> {
+        self.archives.move_data.as_ref()?.get(id)
+    }
+
+    pub fn open_problems(&mut self) {
+        self.problems = Some(Problems::new(check::check(&self.save_file)));
+    }
+
+    /// Starts editing the selected move or personal data field, or toggles the selected machine
+    /// or tutor move.
+    pub fn edit_selected(&mut self) {
+        match self.gui_state.selected {
+            Selected::Move(index) => {
+                self.move_editor = Some(MoveEditor::new(index, self.get_moves()[index]));
+            }
+            Selected::Field(index) => {
+                let field = Field::ALL[index];
+                let value = self.personal_value(field).unwrap_or_default();
+                self.prompt = Some(Prompt::Field(field, value.to_string()));
+            }
+            Selected::Teachable(index) => {
+                if let (Some(&(group, index)), Some(personal)) =
+                    (self.teachables().get(index), self.current_personal())
+                {
+                    let learnable = !personal.can_learn(group, index);
+                    self.apply(Edit::Compatibility {
+                        group,
+                        index,
+                        learnable,
+                    });
+                }
+            }
+            Selected::Evolution(index, part) => self.edit_evolution(index, part),
+            Selected::Pokemon | Selected::EggMove(_) => {}
+        }
+    }
+
+    /// Asks for a new value of a part of an evolution, with the move picker for a move to know.
+    fn edit_evolution(&mut self, index: usize, part: Part) {
+        let Some(evolution) = self.evolution(index) else {
+            return;
+        };
+        match (part, evolution.parameter_kind()) {
+            (Part::Parameter, Parameter::None) => {
+                self.report_error(format!("{} takes no parameter", evolution.method_name()));
+            }
+            (Part::Parameter, Parameter::Move) => {
+                self.move_picker = Some(MovePicker::evolution_move(evolution.parameter));
+            }
+            _ => {
+                let value = part.get(&evolution).to_string();
+                self.prompt = Some(Prompt::Evolution(index, part, value));
+            }
+        }
+    }
+
+    fn evolution_key(&mut self, code: KeyCode) {
+        let Some(Prompt::Evolution(index, part, _)) = self.prompt else {
+            return;
+        };
+        let Some(old) = self.evolution(index) else {
+            return;
+        };
+        let Some(Prompt::Evolution(_, _, input)) = &mut self.prompt else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => _ = input.pop(),
+            KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 5 => input.push(c),
+            KeyCode::Enter => match part.parse(&old, input) {
+                Ok(value) => {
+                    self.prompt = None;
+                    self.set_evolution(index, part.with(old, value));
+                }
+                Err(error) => self.report_error(error),
+            },
+            _ => {}
+        }
+    }
+
+    fn current_personal(&self) -> Option<&Personal> {
+        let personal = self.archives.personal.as_ref()?;
+        personal.pokemons.get(self.gui_state.current_pokemon)
+    }
+
+    fn personal_value(&self, field: Field) -> Option<u16> {
+        Some(self.current_personal()?.get(field))
+    }
+
+    fn field_key(&mut self, code: KeyCode) {
+        let Some(Prompt::Field(field, input)) = &mut self.prompt else {
+            return;
+        };
+        let field = *field;
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => _ = input.pop(),
+            KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 5 => input.push(c),
+            KeyCode::Enter => match field.parse(input) {
+                Ok(new) => {
+                    self.prompt = None;
+                    let old = self.personal_value(field).unwrap_or_default();
+                    if old != new {
+                        self.apply(Edit::Personal { field, old, new });
+                    }
+                }
+                Err(error) => self.report_error(error),
+            },
+            _ => {}
+        }
+    }
+
+    /// Whether key presses go to a popup or an input field rather than the tables.
+    pub const fn is_capturing_input(&self) -> bool {
+        self.move_picker.is_some()
+            || self.learners.is_some()
+            || self.problems.is_some()
+            || self.move_editor.is_some()
+            || self.move_screen.is_some()
+            || self.prompt.is_some()
+    }
+
+    pub fn input_key(&mut self, code: KeyCode) {
+        if let Some(picker) = &mut self.move_picker {
+            match picker.handle_key(code) {
+                Some(move_picker::Outcome::Cancel) => self.move_picker = None,
+                Some(move_picker::Outcome::Confirm(new_move)) => {
+                    self.move_picker = None;
+                    self.insert_move(new_move);
+                }
+                Some(move_picker::Outcome::Choose(id)) => {
+                    let purpose = picker.purpose();
+                    self.move_picker = None;
+                    match (purpose, &mut self.move_editor) {
+                        (Purpose::Swap, Some(editor)) => editor.set_id(id),
+                        (Purpose::AddEggMove, _) => self.add_egg_move(id),
+                        (Purpose::MoveData, _) => self.open_move_screen(id),
+                        (Purpose::EvolutionMove, _) => {
+                            if let Selected::Evolution(index, _) = self.gui_state.selected
+                                && let Some(old) = self.evolution(index)
+                            {
+                                self.set_evolution(index, Part::Parameter.with(old, id));
+                            }
+                        }
+                        _ => self.learners = Some(Learners::new(id, &self.save_file.pokemons)),
+                    }
+                }
+                None => {}
+            }
+        } else if let Some(learners) = &mut self.learners {
+            match learners.handle_key(code) {
+                Some(learners::Outcome::Cancel) => self.learners = None,
+                Some(learners::Outcome::Jump(pokemon, index)) => {
+                    self.learners = None;
+                    self.gui_state = State {
+                        current_pokemon: pokemon,
+                        selected: Selected::Move(index),
+                    };
+                    self.reveal_current_pokemon();
+                    self.sync_tab();
+                }
+                None => {}
+            }
+        } else if let Some(problems) = &mut self.problems {
+            match problems.handle_key(code) {
+                Some(problems::Outcome::Cancel) => self.problems = None,
+                Some(problems::Outcome::Jump(pokemon, index)) => {
+                    self.problems = None;
+                    self.gui_state = State {
+                        current_pokemon: pokemon,
+                        selected: Selected::Move(index),
+                    };
+                    self.reveal_current_pokemon();
+                    self.sync_tab();
+                }
+                None => {}
+            }
+        } else if let Some(screen) = &mut self.move_screen {
+            let id = screen.id;
+            let Some(data) = self
+                .archives
+                .move_data
+                .as_ref()
+                .and_then(|file| file.get(id))
+            else {
+                self.move_screen = None;
+                return;
+            };
+            match screen.handle_key(code, data) {
+                Some(move_screen::Outcome::Close) => self.move_screen = None,
+                Some(move_screen::Outcome::Set(field, new)) => {
+                    let old = data.get(field);
+                    if old != new {
+                        self.apply(Edit::MoveData {
+                            id,
+                            field,
+                            old,
+                            new,
+                        });
+                    }
+                }
+                Some(move_screen::Outcome::Toggle(flag)) => {
+                    let set = !data.has_flag(flag);
+                    self.apply(Edit::MoveFlag { id, flag, set });
+                }
+                None => {}
+            }
+        } else if let Some(editor) = &mut self.move_editor {
+            match editor.handle_key(code) {
+                Some(move_editor::Outcome::Cancel) => self.move_editor = None,
+                Some(move_editor::Outcome::Commit(new)) => {
+                    let index = editor.index;
+                    self.move_editor = None;
+                    let old = self.get_moves()[index];
+                    if old != new {
+                        self.apply(Edit::Replace { index, old, new });
+                    }
+                }
+                Some(move_editor::Outcome::PickMove(id)) => {
+                    self.move_picker = Some(MovePicker::swap(id));
+                }
+                None => {}
+            }
+        } else {
+            match self.prompt {
+                Some(Prompt::Search) => self.search_key(code),
+                Some(Prompt::GoTo(_)) => self.go_to_key(code),
+                Some(Prompt::Field(..)) => self.field_key(code),
+                Some(Prompt::Evolution(..)) => self.evolution_key(code),
+                None => {}
+            }
+        }
+    }
+
+    /// Inserts the move after every move learned at the same level or before.
+    fn insert_move(&mut self, new: Move) {
+        let index = self.get_moves().partition_point(|m| m.level <= new.level);
+        self.apply(Edit::Insert { index, new });
+    }
+
     pub fn render(&self, frame: &mut Frame) {
-        let layout = self.layout().split(frame.area());
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let comparison = self.comparison();
+        let layout = self.layout(comparison.is_some()).split(main);
         frame.render_stateful_widget(self.pokemon_table(), layout[0], &mut self.pokemon_state());
-        frame.render_stateful_widget(self.move_table(), layout[1], &mut self.move_state());
+        let statuses = comparison
+            .as_ref()
+            .map(|comparison| comparison.edited.as_slice());
+        match self.tab {
+            Tab::Learnset => {
+                let [moves, egg_moves] = self.learnset_layout().areas(layout[1]);
+                frame.render_stateful_widget(
+                    self.move_table(statuses),
+                    moves,
+                    &mut self.move_state(),
+                );
+                if self.current_egg_moves().is_some() {
+                    frame.render_stateful_widget(
+                        self.egg_move_table(),
+                        egg_moves,
+                        &mut self.egg_move_state(),
+                    );
+                }
+            }
+            Tab::Personal => frame.render_stateful_widget(
+                self.personal_table(),
+                layout[1],
+                &mut self.field_state(),
+            ),
+            Tab::Teachable => frame.render_stateful_widget(
+                self.teachable_table(),
+                layout[1],
+                &mut self.teachable_state(),
+            ),
+            Tab::Evolutions => frame.render_stateful_widget(
+                self.evolution_table(),
+                layout[1],
+                &mut self.evolution_state(),
+            ),
+        }
+        if let Some(comparison) = &comparison {
+            frame.render_widget(reference_table(comparison), layout[2]);
+        }
+        frame.render_widget(self.status_line(), status);
+        if let Some(learners) = &self.learners {
+            learners.render(frame);
+        }
+        if let Some(problems) = &self.problems {
+            problems.render(frame);
+        }
+        if let Some(screen) = &self.move_screen
+            && let Some(data) = self.move_data(screen.id)
+        {
+            screen.render(frame, data);
+        }
+        if let Some(picker) = &self.move_picker {
+            picker.render(frame);
+        }
+        if self.quit_prompt {
+            render_quit_prompt(frame);
+        }
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        match &self.prompt {
+            Some(Prompt::Search) => return Line::from(format!("/{}_", self.filter)).bold(),
+            Some(Prompt::GoTo(number)) => return Line::from(format!("Go to #{number}_")).bold(),
+            Some(Prompt::Field(field, input)) => {
+                return self.value_prompt(field.name(), &field.range(), input);
+            }
+            Some(Prompt::Evolution(index, part, input)) => {
+                let range = self
+                    .current_evolutions()
+                    .map_or(0..=0, |evolutions| part.range(&evolutions[*index]));
+                return self.value_prompt(part.name(), &range, input);
+            }
+            None => {}
+        }
+        let modified = if self.is_modified() {
+            Span::from("[modified] ").yellow()
+        } else {
+            Span::default()
+        };
+        let problems = match self.problem_count {
+            None => Span::default(),
+            Some((count, severity)) => {
+                let span = Span::from(format!("[{count} problems, ! to list] "));
+                match severity {
+                    Severity::Error => span.red(),
+                    Severity::Warning => span.yellow(),
+                }
+            }
+        };
+        let auto_sort = if self.auto_sort {
+            Span::from("[auto-sort] ")
+        } else {
+            Span::default()
+        };
+        Line::from_iter(
+            [modified, auto_sort, problems]
+                .into_iter()
+                .chain(self.status.clone()),
+        )
+    }
+
+    /// Line where a number is typed, with its name, its range and any error.
+    fn value_prompt(&self, name: &str, range: &RangeInclusive<u16>, input: &str) -> Line<'_> {
+        let prompt = format!("{name} ({}-{}): {input}_ ", range.start(), range.end());
+        Line::from_iter(
+            [Span::from(prompt).bold()]
+                .into_iter()
+                .chain(self.status.clone()),
+        )
     }
 
-    fn layout(&self) -> Layout {
+    fn layout(&self, compare: bool) -> Layout {
+        let constraints = if compare {
+            [30, 35, 35].map(Constraint::Percentage).to_vec()
+        } else {
+            [50, 50].map(Constraint::Percentage).to_vec()
+        };
         Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints(constraints)
+    }
+
+    fn comparison(&self) -> Option<Comparison<'_>> {
+        let reference = self
+            .reference
+            .as_ref()
+            .filter(|_| self.compare && self.tab == Tab::Learnset)?;
+        let moves = reference
+            .save_file
+            .pokemons
+            .get(self.gui_state.current_pokemon)
+            .map_or(&[][..], Vec::as_slice);
+        let (reference_statuses, edited) = diff::statuses(moves, self.get_moves());
+        Some(Comparison {
+            name: &reference.name,
+            moves,
+            reference: reference_statuses,
+            edited,
+        })
     }
 
     fn pokemon_table(&self) -> Table<'_> {
-        let rows = (1..)
-            .zip(POKE_NAMES)
-            .map(|(id, name)| Row::new([id.to_string(), name.to_string()]));
+        let visible = self.visible_pokemons();
+        let title = if self.is_filtered() {
+            format!("Pokemons matching \"{}\" ({})", self.filter, visible.len())
+        } else {
+            String::from("Pokemons")
+        };
+        let rows = visible.into_iter().map(|pokemon| {
+            // Black 2 and White 2 archives have members past the known Pokémon, shown
+            // without a name.
+            let name = POKE_NAMES.get(pokemon).copied().unwrap_or_default();
+            Row::new([(pokemon + 1).to_string(), name.to_string()])
+        });
         let widths = [Constraint::Length(5), Constraint::Length(25)];
         let table = Table::new(rows, widths)
             .header(Row::new(["ID", "Name"]))
-            .block(Block::bordered().title("Pokemons"))
+            .block(Block::bordered().title(title))
             .row_highlight_style(Style::new().reversed())
             .highlight_spacing(HighlightSpacing::Always);
 
@@ -96,25 +1294,48 @@ impl App {
     }
 
     fn pokemon_state(&self) -> TableState {
-        TableState::default().with_selected(self.gui_state.current_pokemon)
+        TableState::default().with_selected(self.pokemon_position(&self.visible_pokemons()))
     }
 
-    fn move_table(&self) -> Table<'_> {
-        let moves = self.get_moves().iter().map(|pmove| {
-            Row::new([
-                pmove.id.to_string(),
-                pmove.name().to_string(),
-                pmove.level.to_string(),
-            ])
-        });
-        let widths = [
+    /// Table of the current learnset, colored by `statuses` in compare mode.
+    fn move_table(&self, statuses: Option<&[Status]>) -> Table<'_> {
+        let marked = self.mark_range();
+        let moves =
+            self.get_moves()
+                .iter()
+                .enumerate()
+                .map(|(index, pmove)| match &self.move_editor {
+                    Some(editor) if editor.index == index => editor.row(),
+                    _ => {
+                        let style = statuses
+                            .map_or_else(Style::new, |statuses| status_style(statuses[index]));
+                        let is_marked = marked.as_ref().is_some_and(|range| range.contains(&index));
+                        move_row(pmove, self.move_data(pmove.id)).style(if is_marked {
+                            style.on_dark_gray()
+                        } else {
+                            style
+                        })
+                    }
+                });
+        let mut widths = vec![
             Constraint::Length(5),
             Constraint::Length(20),
             Constraint::Length(5),
         ];
+        let mut header = vec!["ID", "Name", "Level"];
+        if self.archives.move_data.is_some() {
+            widths.extend([8, 6, 4, 4, 3].map(Constraint::Length));
+            header.extend(MOVE_COLUMNS.map(move_data::Field::short_name));
+        }
+        let block = match &self.move_editor {
+            Some(editor) => Block::bordered()
+                .title("Moves")
+                .title_bottom(editor.status()),
+            None => Block::bordered().title("Moves"),
+        };
         let table = Table::new(moves, widths)
-            .header(Row::new(["ID", "Name", "Level"]))
-            .block(Block::bordered().title("Moves"))
+            .header(Row::new(header))
+            .block(block)
             .row_highlight_style(Style::new().reversed())
             .highlight_spacing(HighlightSpacing::Always);
 
@@ -125,65 +1346,207 @@ impl App {
         }
     }
 
+    /// Learnset above the egg moves, when there are egg moves to show.
+    fn learnset_layout(&self) -> Layout {
+        let egg_moves = self
+            .current_egg_moves()
+            .map_or(0, |egg_moves| (egg_moves.len() + 3).clamp(4, 14));
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(egg_moves as u16)])
+    }
+
+    fn egg_move_table(&self) -> Table<'_> {
+        let rows = self
+            .egg_moves()
+            .iter()
+            .map(|&id| Row::new([id.to_string(), Move::new(id, 0).name().to_string()]));
+        let widths = [Constraint::Length(5), Constraint::Length(20)];
+        let table = Table::new(rows, widths)
+            .header(Row::new(["ID", "Name"]))
+            .block(Block::bordered().title("Egg moves (e to add)"))
+            .row_highlight_style(Style::new().reversed())
+            .highlight_spacing(HighlightSpacing::Always);
+
+        if let Selected::EggMove(_) = self.gui_state.selected {
+            table.highlight_symbol(">>")
+        } else {
+            table
+        }
+    }
+
+    fn egg_move_state(&self) -> TableState {
+        match self.gui_state.selected {
+            Selected::EggMove(index) => TableState::default().with_selected(index),
+            _ => TableState::default(),
+        }
+    }
+
     fn move_state(&self) -> TableState {
         match self.gui_state.selected {
             Selected::Move(index) => {
                 debug_assert!(index < self.get_moves().len());
                 TableState::default().with_selected(index)
             }
-            Selected::Pokemon => TableState::default(),
+            Selected::Pokemon
+            | Selected::Field(_)
+            | Selected::Teachable(_)
+            | Selected::EggMove(_)
+            | Selected::Evolution(..) => TableState::default(),
         }
     }
 
-    fn get_moves(&self) -> &[Move] {
-        debug_assert!(self.gui_state.current_pokemon < self.save_file.pokemons.len());
-        &self.save_file.pokemons[self.gui_state.current_pokemon]
-    }
+    fn personal_table(&self) -> Table<'_> {
+        let rows = Field::ALL.iter().map(|&field| {
+            let (value, meaning) = self
+                .personal_value(field)
+                .map(|value| (value.to_string(), field.describe(value)))
+                .unwrap_or_default();
+            Row::new([field.name().to_string(), value, meaning])
+        });
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+        ];
+        let title = if self.has_personal() {
+            "Personal data"
+        } else {
+            "Personal data (missing for this Pokémon)"
+        };
+        let table = Table::new(rows, widths)
+            .header(Row::new(["Field", "Value", ""]))
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::new().reversed())
+            .highlight_spacing(HighlightSpacing::Always);
 
-    fn get_moves_mut(&mut self) -> &mut Vec<Move> {
-        debug_assert!(self.gui_state.current_pokemon < self.save_file.pokemons.len());
-        &mut self.save_file.pokemons[self.gui_state.current_pokemon]
+        if let Selected::Field(_) = self.gui_state.selected {
+            table.highlight_symbol(">>")
+        } else {
+            table
+        }
     }
-}
 
-impl Default for State {
-    fn default() -> Self {
-        Self {
-            current_pokemon: 0,
-            selected: Selected::Pokemon,
+    fn field_state(&self) -> TableState {
+        match self.gui_state.selected {
+            Selected::Field(index) => TableState::default().with_selected(index),
+            _ => TableState::default(),
         }
     }
-}
 
-impl State {
-    const fn next(&mut self, max_move_len: usize) {
-        if let Selected::Move(index) = self.selected {
-            let index = index + 1;
-            self.selected = Selected::Move(if index >= max_move_len { 0 } else { index })
+    /// Grid of checkboxes telling which machine and tutor moves the current Pokémon can learn.
+    fn teachable_table(&self) -> Table<'_> {
+        let teachables = self.teachables();
+        let personal = self.current_personal();
+        let rows = self.grid().into_iter().map(|row| match row {
+            GridRow::Group(group) => Row::new([Cell::from(group.name()).bold()]),
+            GridRow::Moves(range) => Row::new(teachables[range].iter().map(|&(group, index)| {
+                let learnable = personal.is_some_and(|personal| personal.can_learn(group, index));
+                let checkbox = if learnable { "[x]" } else { "[ ]" };
+                let name = move_id_to_name(group.moves()[index].into());
+                match group.machine(index) {
+                    Some(machine) => format!("{checkbox} {machine} {name}"),
+                    None => format!("{checkbox} {name}"),
+                }
+            })),
+        });
+        let title = if self.has_personal() {
+            "TMs, HMs and tutors (Enter or Space to toggle)"
+        } else {
+            "TMs, HMs and tutors (missing for this Pokémon)"
+        };
+        let table = Table::new(rows, [Constraint::Fill(1); GRID_COLUMNS])
+            .block(Block::bordered().title(title))
+            .cell_highlight_style(Style::new().reversed())
+            .highlight_spacing(HighlightSpacing::Always);
+
+        if let Selected::Teachable(_) = self.gui_state.selected {
+            table.highlight_symbol(">>")
         } else {
-            self.next_pokemon();
+            table
         }
     }
-    fn previous(&mut self, max_move_len: usize) {
-        if let Selected::Move(index) = self.selected {
-            self.selected = Selected::Move(index.checked_sub(1).unwrap_or(max_move_len - 1));
+
+    fn teachable_state(&self) -> TableState {
+        let Selected::Teachable(index) = self.gui_state.selected else {
+            return TableState::default();
+        };
+        let grid = self.grid();
+        let row = grid.iter().position(|row| match row {
+            GridRow::Moves(range) => range.contains(&index),
+            GridRow::Group(_) => false,
+        });
+        let column = grid.iter().find_map(|row| match row {
+            GridRow::Moves(range) if range.contains(&index) => Some(index - range.start),
+            _ => None,
+        });
+        TableState::default()
+            .with_selected(row)
+            .with_selected_column(column)
+    }
+
+    fn evolution_table(&self) -> Table<'_> {
+        let evolutions = self.current_evolutions();
+        let rows = (0..EVOLUTION_COUNT).map(|index| {
+            let cells = evolutions.map(|evolutions| &evolutions[index]).map_or_else(
+                || [String::new(), String::new(), String::new()],
+                |evolution| match evolution.method {
+                    0 => [evolution.method_name(), String::new(), String::new()],
+                    _ => [
+                        evolution.method_name(),
+                        evolution.parameter_name(),
+                        evolution.target_name(),
+                    ],
+                },
+            );
+            Row::new(cells)
+        });
+        let widths = [
+            Constraint::Length(24),
+            Constraint::Length(16),
+            Constraint::Fill(1),
+        ];
+        let title = if evolutions.is_some() {
+            "Evolutions"
+        } else {
+            "Evolutions (missing for this Pokémon)"
+        };
+        let table = Table::new(rows, widths)
+            .header(Row::new(Part::ALL.map(Part::name)))
+            .block(Block::bordered().title(title))
+            .cell_highlight_style(Style::new().reversed())
+            .highlight_spacing(HighlightSpacing::Always);
+
+        if let Selected::Evolution(..) = self.gui_state.selected {
+            table.highlight_symbol(">>")
         } else {
-            self.previous_pokemon();
+            table
         }
     }
-    const fn next_pokemon(&mut self) {
-        self.current_pokemon += 1;
-        if self.current_pokemon >= POKE_NAMES.len() {
-            self.current_pokemon = 0;
+
+    fn evolution_state(&self) -> TableState {
+        match self.gui_state.selected {
+            Selected::Evolution(index, part) => TableState::default()
+                .with_selected(index)
+                .with_selected_column(Part::ALL.iter().position(|&p| p == part)),
+            _ => TableState::default(),
         }
     }
 
-    const fn previous_pokemon(&mut self) {
-        self.current_pokemon = match self.current_pokemon.checked_sub(1) {
-            Some(value) => value,
-            None => POKE_NAMES.len() - 1,
+    fn get_moves(&self) -> &[Move] {
+        debug_assert!(self.gui_state.current_pokemon < self.save_file.pokemons.len());
+        &self.save_file.pokemons[self.gui_state.current_pokemon]
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            current_pokemon: 0,
+            selected: Selected::Pokemon,
         }
     }
+}
+
+impl State {
     const fn select_pokemon(&mut self) {
         self.selected = Selected::Pokemon;
     }
@@ -192,3 +1555,107 @@ impl State {
         self.selected = Selected::Move(0);
     }
 }
+
+/// Row of a learnset table, followed by the [`MOVE_COLUMNS`] of the move when `data` is given.
+fn move_row(pmove: &Move, data: Option<&MoveData>) -> Row<'static> {
+    let columns = data
+        .into_iter()
+        .flat_map(|data| MOVE_COLUMNS.map(|field| field.short(data.get(field))));
+    Row::new(
+        [
+            pmove.id.to_string(),
+            pmove.name().to_string(),
+            pmove.level.to_string(),
+        ]
+        .into_iter()
+        .chain(columns),
+    )
+}
+
+fn status_style(status: Status) -> Style {
+    match status {
+        Status::Unchanged => Style::new(),
+        Status::Added => Style::new().green(),
+        Status::Removed => Style::new().red(),
+        Status::Releveled => Style::new().yellow(),
+    }
+}
+
+fn reference_table<'a>(comparison: &Comparison<'a>) -> Table<'a> {
+    let rows = comparison
+        .moves
+        .iter()
+        .zip(&comparison.reference)
+        .map(|(pmove, &status)| move_row(pmove, None).style(status_style(status)));
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Length(20),
+        Constraint::Length(5),
+    ];
+    let legend = Line::from(vec![
+        "added".green(),
+        " removed".red(),
+        " re-leveled".yellow(),
+    ]);
+    Table::new(rows, widths)
+        .header(Row::new(["ID", "Name", "Level"]))
+        .block(
+            Block::bordered()
+                .title(format!("Reference: {}", comparison.name))
+                .title_bottom(legend),
+        )
+        .highlight_spacing(HighlightSpacing::Always)
+}
+
+fn render_quit_prompt(frame: &mut Frame) {
+    let area = popup_area(frame.area(), Constraint::Length(44), Constraint::Length(4));
+    let prompt = Paragraph::new(vec![
+        Line::from("There are unsaved changes."),
+        Line::from("s: save and quit, d: discard, c: cancel"),
+    ])
+    .block(Block::bordered().title("Quit"));
+    frame.render_widget(Clear, area);
+    frame.render_widget(prompt, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorting_every_learnset_is_a_single_step_of_the_history() {
+        let unsorted = vec![
+            vec![Move::new(33, 9), Move::new(45, 1)],
+            vec![Move::new(33, 1)],
+            vec![Move::new(22, 7), Move::new(10, 3)],
+        ];
+        let mut app = App::from_save_file(
+            SaveFile::from_learnsets(unsorted.clone()),
+            Archives::default(),
+        );
+
+        app.sort_all();
+        assert_eq!(
+            app.save_file.pokemons[0],
+            [Move::new(45, 1), Move::new(33, 9)]
+        );
+        assert_eq!(
+            app.save_file.pokemons[2],
+            [Move::new(10, 3), Move::new(22, 7)]
+        );
+
+        app.undo();
+        assert_eq!(app.save_file.pokemons, unsorted);
+        assert!(!app.is_modified());
+
+        app.redo();
+        assert_eq!(
+            app.save_file.pokemons[0],
+            [Move::new(45, 1), Move::new(33, 9)]
+        );
+        assert_eq!(
+            app.save_file.pokemons[2],
+            [Move::new(10, 3), Move::new(22, 7)]
+        );
+    }
+}
@@ -0,0 +1,430 @@
+use crate::{
+    archives::Archives,
+    evolution::{EVOLUTION_COUNT, Evolution},
+    file::{Move, SaveFile},
+    move_data::{self, Flag, MoveData},
+    personal::{Compatibility, Field, Personal},
+};
+
+/// A single modification of one Pokémon's learnset or other data.
+pub enum Edit {
+    Insert {
+        index: usize,
+        new: Move,
+    },
+    Delete {
+        index: usize,
+        old: Move,
+    },
+    Replace {
+        index: usize,
+        old: Move,
+        new: Move,
+    },
+    /// Replacement of the whole learnset, such as when pasting one.
+    Set {
+        old: Vec<Move>,
+        new: Vec<Move>,
+    },
+    Personal {
+        field: Field,
+        old: u16,
+        new: u16,
+    },
+    /// Toggling whether a machine or tutor move can be taught.
+    Compatibility {
+        group: Compatibility,
+        index: usize,
+        learnable: bool,
+    },
+    EggMoves {
+        old: Vec<u16>,
+        new: Vec<u16>,
+    },
+    Evolution {
+        index: usize,
+        old: Evolution,
+        new: Evolution,
+    },
+    /// Change to the data of a move, which every Pokémon shares.
+    MoveData {
+        id: u16,
+        field: move_data::Field,
+        old: i32,
+        new: i32,
+    },
+    MoveFlag {
+        id: u16,
+        flag: Flag,
+        set: bool,
+    },
+}
+
+impl Edit {
+    /// Applies the edit to the data of `pokemon`.
+    pub fn apply(&self, pokemon: usize, save_file: &mut SaveFile, archives: &mut Archives) {
+        let moves = &mut save_file.pokemons[pokemon];
+        match self {
+            &Self::Insert { index, new } => moves.insert(index, new),
+            &Self::Delete { index, .. } => _ = moves.remove(index),
+            &Self::Replace { index, new, .. } => moves[index] = new,
+            Self::Set { new, .. } => moves.clone_from(new),
+            &Self::Personal { field, new, .. } => personal(archives, pokemon).set(field, new),
+            &Self::Compatibility {
+                group,
+                index,
+                learnable,
+            } => personal(archives, pokemon).set_can_learn(group, index, learnable),
+            Self::EggMoves { new, .. } => egg_moves(archives, pokemon).clone_from(new),
+            &Self::Evolution { index, new, .. } => evolutions(archives, pokemon)[index] = new,
+            &Self::MoveData { id, field, new, .. } => move_data(archives, id).set(field, new),
+            &Self::MoveFlag { id, flag, set } => move_data(archives, id).set_flag(flag, set),
+        }
+    }
+
+    pub fn revert(&self, pokemon: usize, save_file: &mut SaveFile, archives: &mut Archives) {
+        let moves = &mut save_file.pokemons[pokemon];
+        match self {
+            &Self::Insert { index, .. } => _ = moves.remove(index),
+            &Self::Delete { index, old } => moves.insert(index, old),
+            &Self::Replace { index, old, .. } => moves[index] = old,
+            Self::Set { old, .. } => moves.clone_from(old),
+            &Self::Personal { field, old, .. } => personal(archives, pokemon).set(field, old),
+            &Self::Compatibility {
+                group,
+                index,
+                learnable,
+            } => personal(archives, pokemon).set_can_learn(group, index, !learnable),
+            Self::EggMoves { old, .. } => egg_moves(archives, pokemon).clone_from(old),
+            &Self::Evolution { index, old, .. } => evolutions(archives, pokemon)[index] = old,
+            &Self::MoveData { id, field, old, .. } => move_data(archives, id).set(field, old),
+            &Self::MoveFlag { id, flag, set } => move_data(archives, id).set_flag(flag, !set),
+        }
+    }
+}
+
+fn personal(archives: &mut Archives, pokemon: usize) -> &mut Personal {
+    let personal = archives.personal.as_mut();
+    &mut personal
+        .expect("personal data is edited once loaded")
+        .pokemons[pokemon]
+}
+
+fn egg_moves(archives: &mut Archives, pokemon: usize) -> &mut Vec<u16> {
+    let egg_moves = archives.egg_moves.as_mut();
+    &mut egg_moves
+        .expect("egg moves are edited once loaded")
+        .pokemons[pokemon]
+}
+
+fn evolutions(archives: &mut Archives, pokemon: usize) -> &mut [Evolution; EVOLUTION_COUNT] {
+    let evolutions = archives.evolutions.as_mut();
+    &mut evolutions
+        .expect("evolutions are edited once loaded")
+        .pokemons[pokemon]
+}
+
+fn move_data(archives: &mut Archives, id: u16) -> &mut MoveData {
+    let move_data = archives.move_data.as_mut();
+    &mut move_data.expect("move data is edited once loaded").moves[usize::from(id)]
+}
+
+/// An [`Edit`] along with where it happened and the interface state around it.
+pub struct Change<S> {
+    pub pokemon: usize,
+    pub edit: Edit,
+    pub before: S,
+    pub after: S,
+}
+
+/// Undo and redo stacks, each step holding one or more changes undone together.
+pub struct History<S> {
+    undo: Vec<Vec<Change<S>>>,
+    redo: Vec<Vec<Change<S>>>,
+}
+
+impl<S> History<S> {
+    pub const fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records changes that were just applied as a single step, which makes the redo stack
+    /// obsolete.
+    pub fn record(&mut self, changes: Vec<Change<S>>) {
+        if !changes.is_empty() {
+            self.undo.push(changes);
+            self.redo.clear();
+        }
+    }
+
+    /// Returns the changes to revert, last one first, moving them to the redo stack.
+    pub fn undo(&mut self) -> Option<&[Change<S>]> {
+        let changes = self.undo.pop()?;
+        self.redo.push(changes);
+        self.redo.last().map(Vec::as_slice)
+    }
+
+    /// Returns the changes to apply again, first one first, moving them back to the undo stack.
+    pub fn redo(&mut self) -> Option<&[Change<S>]> {
+        let changes = self.redo.pop()?;
+        self.undo.push(changes);
+        self.undo.last().map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        egg_moves::EggMoveFile, evolution::EvolutionFile, move_data::MoveDataFile, narc::Envelope,
+        personal::PersonalFile,
+    };
+
+    fn moves() -> Vec<Move> {
+        vec![Move::new(33, 1), Move::new(45, 5), Move::new(22, 9)]
+    }
+
+    /// Data of a single Pokémon: the learnset of [`moves`], zeroed personal data, Tackle as its
+    /// only egg move and no evolution, along with zeroed data for move 0 and move 1.
+    fn data() -> (SaveFile, Archives) {
+        let personal = Envelope::unnamed(&[]).to_binary_format(&[[0; 0x3c]]);
+        let egg_moves = Envelope::unnamed(&[]).to_binary_format(&[[1, 0, 33, 0]]);
+        let evolutions = Envelope::unnamed(&[]).to_binary_format(&[[0; 42]]);
+        let move_data = Envelope::unnamed(&[0; 0x24]).to_binary_format(&[[0; 0x24]]);
+        let archives = Archives {
+            personal: Some(PersonalFile::try_from(personal.as_slice()).unwrap()),
+            egg_moves: Some(EggMoveFile::try_from(egg_moves.as_slice()).unwrap()),
+            evolutions: Some(EvolutionFile::try_from(evolutions.as_slice()).unwrap()),
+            move_data: Some(MoveDataFile::try_from(move_data.as_slice()).unwrap()),
+        };
+        (SaveFile::from_learnsets(vec![moves()]), archives)
+    }
+
+    /// Applies `edit`, checks the result, then reverts it back to the original learnset.
+    fn round_trip(edit: &Edit, expected: &[Move]) {
+        let (mut save_file, mut archives) = data();
+        edit.apply(0, &mut save_file, &mut archives);
+        assert_eq!(save_file.pokemons[0], expected);
+        edit.revert(0, &mut save_file, &mut archives);
+        assert_eq!(save_file.pokemons[0], moves());
+    }
+
+    fn change(pokemon: usize, before: usize, after: usize) -> Change<usize> {
+        Change {
+            pokemon,
+            edit: Edit::Insert {
+                index: 0,
+                new: Move::new(1, 1),
+            },
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn inserts_are_reverted() {
+        let new = Move::new(10, 3);
+        let edit = Edit::Insert { index: 1, new };
+        round_trip(&edit, &[moves()[0], new, moves()[1], moves()[2]]);
+    }
+
+    #[test]
+    fn deletions_are_reverted() {
+        let edit = Edit::Delete {
+            index: 1,
+            old: moves()[1],
+        };
+        round_trip(&edit, &[moves()[0], moves()[2]]);
+    }
+
+    #[test]
+    fn replacements_are_reverted() {
+        let new = Move::new(10, 3);
+        let edit = Edit::Replace {
+            index: 2,
+            old: moves()[2],
+            new,
+        };
+        round_trip(&edit, &[moves()[0], moves()[1], new]);
+    }
+
+    #[test]
+    fn whole_learnsets_are_reverted() {
+        let new = vec![Move::new(10, 3)];
+        let edit = Edit::Set {
+            old: moves(),
+            new: new.clone(),
+        };
+        round_trip(&edit, &new);
+    }
+
+    #[test]
+    fn personal_fields_are_reverted() {
+        let (mut save_file, mut archives) = data();
+        let catch_rate = |archives: &Archives| {
+            archives.personal.as_ref().unwrap().pokemons[0].get(Field::CatchRate)
+        };
+        let edit = Edit::Personal {
+            field: Field::CatchRate,
+            old: 0,
+            new: 45,
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert_eq!(catch_rate(&archives), 45);
+        assert_eq!(save_file.pokemons[0], moves());
+        edit.revert(0, &mut save_file, &mut archives);
+        assert_eq!(catch_rate(&archives), 0);
+    }
+
+    #[test]
+    fn compatibility_bits_are_reverted() {
+        let (mut save_file, mut archives) = data();
+        let can_learn = |archives: &Archives| {
+            let personal = &archives.personal.as_ref().unwrap().pokemons[0];
+            personal.can_learn(Compatibility::Machines, 3)
+        };
+        let edit = Edit::Compatibility {
+            group: Compatibility::Machines,
+            index: 3,
+            learnable: true,
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert!(can_learn(&archives));
+        edit.revert(0, &mut save_file, &mut archives);
+        assert!(!can_learn(&archives));
+    }
+
+    #[test]
+    fn egg_moves_are_reverted() {
+        let (mut save_file, mut archives) = data();
+        let edit = Edit::EggMoves {
+            old: vec![33],
+            new: vec![33, 45],
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert_eq!(archives.egg_moves.as_ref().unwrap().pokemons[0], [33, 45]);
+        edit.revert(0, &mut save_file, &mut archives);
+        assert_eq!(archives.egg_moves.as_ref().unwrap().pokemons[0], [33]);
+    }
+
+    #[test]
+    fn evolutions_are_reverted() {
+        let (mut save_file, mut archives) = data();
+        let new = Evolution {
+            method: 4,
+            parameter: 16,
+            target: 2,
+        };
+        let edit = Edit::Evolution {
+            index: 1,
+            old: Evolution::default(),
+            new,
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert_eq!(archives.evolutions.as_ref().unwrap().pokemons[0][1], new);
+        edit.revert(0, &mut save_file, &mut archives);
+        assert_eq!(
+            archives.evolutions.as_ref().unwrap().pokemons[0],
+            [Evolution::default(); EVOLUTION_COUNT]
+        );
+    }
+
+    #[test]
+    fn move_data_is_reverted() {
+        let (mut save_file, mut archives) = data();
+        let power = |archives: &Archives| {
+            let move_data = archives.move_data.as_ref().unwrap();
+            move_data.get(1).unwrap().get(move_data::Field::Power)
+        };
+        let edit = Edit::MoveData {
+            id: 1,
+            field: move_data::Field::Power,
+            old: 0,
+            new: 40,
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert_eq!(power(&archives), 40);
+        edit.revert(0, &mut save_file, &mut archives);
+        assert_eq!(power(&archives), 0);
+    }
+
+    #[test]
+    fn move_flags_are_reverted() {
+        let (mut save_file, mut archives) = data();
+        let contact = |archives: &Archives| {
+            let move_data = archives.move_data.as_ref().unwrap();
+            move_data.get(1).unwrap().has_flag(Flag::Contact)
+        };
+        let edit = Edit::MoveFlag {
+            id: 1,
+            flag: Flag::Contact,
+            set: true,
+        };
+
+        edit.apply(0, &mut save_file, &mut archives);
+        assert!(contact(&archives));
+        edit.revert(0, &mut save_file, &mut archives);
+        assert!(!contact(&archives));
+    }
+
+    #[test]
+    fn undo_and_redo_give_back_the_selection_around_the_change() {
+        let mut history = History::new();
+        history.record(vec![change(4, 1, 2)]);
+
+        let undone = &history.undo().unwrap()[0];
+        assert_eq!((undone.pokemon, undone.before), (4, 1));
+        assert!(history.undo().is_none());
+
+        let redone = &history.redo().unwrap()[0];
+        assert_eq!((redone.pokemon, redone.after), (4, 2));
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn changes_recorded_together_are_undone_together() {
+        let mut history = History::new();
+        history.record(vec![change(0, 0, 1)]);
+        history.record(vec![change(1, 1, 2), change(2, 2, 3)]);
+
+        let pokemons = |changes: &[Change<usize>]| {
+            changes
+                .iter()
+                .map(|change| change.pokemon)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pokemons(history.undo().unwrap()), [1, 2]);
+        assert_eq!(pokemons(history.redo().unwrap()), [1, 2]);
+    }
+
+    #[test]
+    fn empty_steps_are_not_recorded() {
+        let mut history = History::new();
+        history.record(vec![change(0, 0, 1)]);
+        history.undo();
+        history.record(Vec::new());
+
+        assert!(history.redo().is_some());
+    }
+
+    #[test]
+    fn new_changes_clear_the_redo_stack() {
+        let mut history = History::new();
+        history.record(vec![change(0, 0, 1)]);
+        history.record(vec![change(0, 1, 2)]);
+        history.undo();
+        history.record(vec![change(0, 1, 3)]);
+
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap()[0].after, 3);
+        assert_eq!(history.undo().unwrap()[0].after, 1);
+    }
+}
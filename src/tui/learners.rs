@@ -0,0 +1,102 @@
+use super::popup_area;
+use crate::{
+    file::Move,
+    names::{POKE_NAMES, move_id_to_name},
+};
+use ratatui::{
+    Frame,
+    crossterm::event::KeyCode,
+    layout::Constraint,
+    style::{Style, Stylize},
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, Paragraph},
+};
+
+/// Popup listing every Pokémon learning a move, to jump to their learnset.
+pub struct Learners {
+    id: u16,
+    /// Pokémon index, index of the move in its learnset, and level it is learned at.
+    entries: Vec<(usize, usize, u16)>,
+    selected: usize,
+}
+
+pub enum Outcome {
+    Cancel,
+    /// Show the learnset of a Pokémon, with the move at the given index selected.
+    Jump(usize, usize),
+}
+
+impl Learners {
+    /// Looks the move `id` up in `pokemons`, listing a Pokémon once per time it learns it.
+    pub fn new(id: u16, pokemons: &[Vec<Move>]) -> Self {
+        let entries = pokemons
+            .iter()
+            .enumerate()
+            .flat_map(|(pokemon, moves)| {
+                moves
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pmove)| pmove.id == id)
+                    .map(move |(index, pmove)| (pokemon, index, pmove.level))
+            })
+            .collect();
+        Self {
+            id,
+            entries,
+            selected: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Outcome> {
+        match code {
+            KeyCode::Esc => return Some(Outcome::Cancel),
+            KeyCode::Enter => {
+                return self
+                    .entries
+                    .get(self.selected)
+                    .map(|&(pokemon, index, _)| Outcome::Jump(pokemon, index));
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup_area(
+            frame.area(),
+            Constraint::Length(50),
+            Constraint::Percentage(60),
+        );
+        let block = Block::bordered()
+            .title(format!(
+                "Learning {} ({})",
+                move_id_to_name(self.id.into()),
+                self.entries.len()
+            ))
+            .title_bottom("Enter: go to learnset, Esc: close");
+        frame.render_widget(Clear, area);
+
+        if self.entries.is_empty() {
+            let message = Paragraph::new("No Pokémon learns this move.").block(block);
+            frame.render_widget(message, area);
+            return;
+        }
+        let items = self.entries.iter().map(|&(pokemon, _, level)| {
+            format!(
+                "{:4} {:<25} level {level}",
+                pokemon + 1,
+                POKE_NAMES.get(pokemon).copied().unwrap_or_default()
+            )
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
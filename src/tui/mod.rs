@@ -1,30 +1,118 @@
+use std::fmt::Display;
+
 use app::App;
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
     DefaultTerminal,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Rect},
 };
 
-use crate::file::SaveFile;
+use crate::{archives::Archives, file::SaveFile};
 
 mod app;
+mod history;
+mod learners;
+mod move_editor;
+mod move_picker;
+mod move_screen;
+mod problems;
+
+/// How the editor starts.
+pub struct Options {
+    /// Named file whose learnsets are shown next to the edited ones for comparison.
+    pub reference: Option<(String, SaveFile)>,
+    /// Sort learnsets by level after every edit.
+    pub auto_sort: bool,
+}
 
-pub fn run(mut terminal: DefaultTerminal, save_file: SaveFile) -> SaveFile {
-    let mut app = App::from_save_file(save_file);
+/// Runs the editor until the user quits, calling `save` whenever they ask to save their changes.
+pub fn run<E: Display>(
+    mut terminal: DefaultTerminal,
+    save_file: SaveFile,
+    archives: Archives,
+    options: Options,
+    mut save: impl FnMut(&SaveFile, &Archives) -> Result<(), E>,
+) {
+    let mut app = App::from_save_file(save_file, archives);
+    if let Some((name, reference)) = options.reference {
+        app.set_reference(name, reference);
+    }
+    if options.auto_sort {
+        app.toggle_auto_sort();
+    }
+    let mut save = |app: &mut App| match save(&app.save_file, &app.archives) {
+        Ok(()) => {
+            app.mark_saved();
+            true
+        }
+        Err(error) => {
+            app.report_error(error);
+            false
+        }
+    };
     loop {
         terminal.draw(|frame| app.render(frame)).unwrap();
         match event::read().unwrap() {
+            Event::Key(key) if key.kind == KeyEventKind::Press && app.is_quit_prompt_open() => {
+                match key.code {
+                    KeyCode::Char('s') => {
+                        app.close_quit_prompt();
+                        if save(&mut app) {
+                            break;
+                        }
+                    }
+                    KeyCode::Char('d') => break,
+                    KeyCode::Char('c') | KeyCode::Esc => app.close_quit_prompt(),
+                    _ => {}
+                }
+            }
+            Event::Key(key) if key.kind == KeyEventKind::Press && app.is_capturing_input() => {
+                app.input_key(key.code);
+            }
             Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc if app.is_filtered() => app.clear_filter(),
+                KeyCode::Char('q') | KeyCode::Esc if app.is_modified() => app.open_quit_prompt(),
                 KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    save(&mut app);
+                }
                 KeyCode::Up => app.select_previous(),
                 KeyCode::Down => app.select_next(),
                 KeyCode::Right => app.select_right(),
                 KeyCode::Left => app.select_left(),
+                KeyCode::PageDown => app.select_page_down(),
+                KeyCode::PageUp => app.select_page_up(),
+                KeyCode::Home => app.select_first(),
+                KeyCode::End => app.select_last(),
+                KeyCode::Char('/') => app.open_search(),
+                KeyCode::Char(c) if c.is_ascii_digit() => app.open_go_to(c),
                 KeyCode::Char('d') => app.delete_move_selected(),
+                KeyCode::Char('a') => app.open_move_picker(),
+                KeyCode::Char('e') => app.open_egg_move_picker(),
+                KeyCode::Char('l') => app.open_move_lookup(),
+                KeyCode::Char('m') => app.open_move_data(),
+                KeyCode::Char('!') => app.open_problems(),
+                KeyCode::Enter | KeyCode::Char(' ') => app.edit_selected(),
+                KeyCode::Tab => app.toggle_tab(),
+                KeyCode::Char('u') => app.undo(),
+                KeyCode::Char('o') => app.sort_learnset(),
+                KeyCode::Char('O') => app.sort_all(),
+                KeyCode::Char('S') => app.toggle_auto_sort(),
+                KeyCode::Char('v') => app.toggle_mark(),
+                KeyCode::Char('y') => app.yank(),
+                KeyCode::Char('p') => app.paste_replace(),
+                KeyCode::Char('P') => app.paste_merge(),
+                KeyCode::Char('c') => app.toggle_compare(),
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.redo(),
                 _ => {}
             },
             _ => {}
         }
     }
+}
 
-    app.save_file
+fn popup_area(area: Rect, width: Constraint, height: Constraint) -> Rect {
+    let [area] = Layout::vertical([height]).flex(Flex::Center).areas(area);
+    let [area] = Layout::horizontal([width]).flex(Flex::Center).areas(area);
+    area
 }
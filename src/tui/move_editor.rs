@@ -0,0 +1,71 @@
+use super::move_picker::parse_level;
+use crate::{file::Move, names::MOVE_NAMES};
+use ratatui::{
+    crossterm::event::KeyCode,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::Row,
+};
+
+/// In-place edition of a single entry of the moves table.
+pub struct MoveEditor {
+    pub index: usize,
+    id: u16,
+    level: String,
+}
+
+pub enum Outcome {
+    Cancel,
+    Commit(Move),
+    PickMove(u16),
+}
+
+impl MoveEditor {
+    pub fn new(index: usize, edited: Move) -> Self {
+        Self {
+            index,
+            id: edited.id,
+            level: edited.level.to_string(),
+        }
+    }
+
+    pub const fn set_id(&mut self, id: u16) {
+        self.id = id;
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Outcome> {
+        match code {
+            KeyCode::Esc => return Some(Outcome::Cancel),
+            KeyCode::Tab => return Some(Outcome::PickMove(self.id)),
+            KeyCode::Enter => return self.validate().ok().map(Outcome::Commit),
+            KeyCode::Backspace => _ = self.level.pop(),
+            KeyCode::Char(c) if c.is_ascii_digit() && self.level.len() < 3 => self.level.push(c),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn validate(&self) -> Result<Move, String> {
+        if !(1..=MOVE_NAMES.len()).contains(&usize::from(self.id)) {
+            return Err(String::from("unknown move, press Tab to choose one"));
+        }
+        parse_level(&self.level).map(|level| Move::new(self.id, level))
+    }
+
+    pub fn row(&self) -> Row<'static> {
+        Row::new([
+            self.id.to_string(),
+            Move::name_of(self.id).to_string(),
+            format!("{}_", self.level),
+        ])
+        .style(Style::new().yellow())
+    }
+
+    /// Validation result shown under the moves table while editing.
+    pub fn status(&self) -> Line<'static> {
+        match self.validate() {
+            Ok(_) => Line::from("Enter: save, Tab: change move, Esc: cancel"),
+            Err(error) => Line::from(error).red(),
+        }
+    }
+}
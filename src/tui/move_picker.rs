@@ -0,0 +1,278 @@
+use super::popup_area;
+use crate::{
+    file::{MAX_LEVEL, MIN_LEVEL, Move},
+    input::parse_in_range,
+    names::MOVE_NAMES,
+};
+use ratatui::{
+    Frame,
+    crossterm::event::KeyCode,
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, Paragraph},
+};
+
+/// Popup used to choose a move from [`MOVE_NAMES`], and the level it is learned at when adding one.
+pub struct MovePicker {
+    query: String,
+    /// `None` when only the move is being chosen, to swap the one of an existing entry.
+    level: Option<String>,
+    purpose: Purpose,
+    focus: Focus,
+    selected: usize,
+    error: Option<String>,
+}
+
+/// What the chosen move is for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    Add,
+    Swap,
+    Lookup,
+    AddEggMove,
+    EvolutionMove,
+    MoveData,
+}
+
+impl Purpose {
+    const fn title(self) -> &'static str {
+        match self {
+            Self::Add => "Add move",
+            Self::Swap => "Change move",
+            Self::Lookup => "Find Pokémon learning",
+            Self::AddEggMove => "Add egg move",
+            Self::EvolutionMove => "Move to know to evolve",
+            Self::MoveData => "Edit data of move",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Focus {
+    Search,
+    Level,
+}
+
+pub enum Outcome {
+    Cancel,
+    Confirm(Move),
+    Choose(u16),
+}
+
+impl MovePicker {
+    pub const fn new() -> Self {
+        Self {
+            query: String::new(),
+            level: Some(String::new()),
+            purpose: Purpose::Add,
+            focus: Focus::Search,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// Picker choosing a replacement for the move `current_id`, which starts selected.
+    pub fn swap(current_id: u16) -> Self {
+        let selected = usize::from(current_id).saturating_sub(1);
+        Self {
+            level: None,
+            purpose: Purpose::Swap,
+            selected: selected.min(MOVE_NAMES.len() - 1),
+            ..Self::new()
+        }
+    }
+
+    /// Picker choosing a move to list the Pokémon learning it.
+    pub fn lookup() -> Self {
+        Self {
+            level: None,
+            purpose: Purpose::Lookup,
+            ..Self::new()
+        }
+    }
+
+    /// Picker choosing a move to show and edit the data of.
+    pub fn move_data() -> Self {
+        Self {
+            level: None,
+            purpose: Purpose::MoveData,
+            ..Self::new()
+        }
+    }
+
+    /// Picker choosing an egg move to add, which has no level.
+    pub fn egg_move() -> Self {
+        Self {
+            level: None,
+            purpose: Purpose::AddEggMove,
+            ..Self::new()
+        }
+    }
+
+    /// Picker choosing the move known by the Pokémon when evolving, `current_id` starting
+    /// selected.
+    pub fn evolution_move(current_id: u16) -> Self {
+        Self {
+            purpose: Purpose::EvolutionMove,
+            ..Self::swap(current_id)
+        }
+    }
+
+    pub const fn purpose(&self) -> Purpose {
+        self.purpose
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Outcome> {
+        match code {
+            KeyCode::Esc => return Some(Outcome::Cancel),
+            KeyCode::Tab | KeyCode::BackTab => self.toggle_focus(),
+            KeyCode::Enter if self.level.is_none() => return self.chosen().map(Outcome::Choose),
+            KeyCode::Enter if self.focus == Focus::Search => self.focus = Focus::Level,
+            KeyCode::Enter => return self.confirm().map(Outcome::Confirm),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches().len().saturating_sub(1));
+            }
+            KeyCode::Backspace if self.focus == Focus::Search => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Backspace => _ = self.level.as_mut().and_then(String::pop),
+            KeyCode::Char(c) if self.focus == Focus::Search => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                if let Some(level) = &mut self.level {
+                    level.push(c);
+                }
+            }
+            _ => {}
+        }
+        self.error = None;
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup_area(
+            frame.area(),
+            Constraint::Length(50),
+            Constraint::Percentage(60),
+        );
+        let block = match self.level {
+            Some(_) => Block::bordered()
+                .title(self.purpose.title())
+                .title_bottom("Enter: confirm, Tab: switch field, Esc: cancel"),
+            None => Block::bordered()
+                .title(self.purpose.title())
+                .title_bottom("Enter: confirm, Esc: cancel"),
+        };
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let [search, list, level] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        frame.render_widget(
+            self.field_line("Search: ", &self.query, Focus::Search),
+            search,
+        );
+
+        let matches = self.matches();
+        let items = matches.iter().map(|&(id, name)| format!("{id:4} {name}"));
+        let list_widget = List::new(items)
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state =
+            ListState::default().with_selected((!matches.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(list_widget, list, &mut state);
+
+        let mut status_line = match &self.level {
+            Some(input) => self.field_line("Level:  ", input, Focus::Level),
+            None => Line::default(),
+        };
+        if let Some(error) = &self.error {
+            status_line.push_span(format!("  {error}").red());
+        }
+        frame.render_widget(Paragraph::new(status_line), level);
+    }
+
+    fn field_line<'a>(&self, label: &'a str, value: &'a str, focus: Focus) -> Line<'a> {
+        let line = Line::from(vec![label.into(), value.into()]);
+        if self.focus == focus {
+            line.bold()
+        } else {
+            line
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Search if self.level.is_some() => Focus::Level,
+            _ => Focus::Search,
+        };
+    }
+
+    fn chosen(&mut self) -> Option<u16> {
+        let chosen = self.matches().get(self.selected).map(|&(id, _)| id);
+        if chosen.is_none() {
+            self.error = Some(String::from("no move matches the search"));
+        }
+        chosen
+    }
+
+    fn confirm(&mut self) -> Option<Move> {
+        let id = self.chosen()?;
+        match parse_level(self.level.as_deref().unwrap_or_default()) {
+            Ok(level) => Some(Move::new(id, level)),
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+
+    /// Moves whose name fuzzy-matches the query, best matches first.
+    fn matches(&self) -> Vec<(u16, &'static str)> {
+        let mut matches: Vec<_> = (1..)
+            .zip(MOVE_NAMES)
+            .filter_map(|(id, name)| fuzzy_score(&self.query, name).map(|score| (score, id, name)))
+            .collect();
+        matches.sort_by_key(|&(score, id, _)| (score, id));
+        matches
+            .into_iter()
+            .map(|(_, id, name)| (id, name))
+            .collect()
+    }
+}
+
+pub fn parse_level(input: &str) -> Result<u16, String> {
+    parse_in_range("level", &(MIN_LEVEL..=MAX_LEVEL), input)
+}
+
+/// Scores `candidate` against `query` when every character of the query appears in it in order,
+/// ignoring case. Lower is better: matches starting early and with few gaps come first.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut candidate = candidate
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .enumerate();
+    let mut previous = None;
+    for wanted in query.chars().map(|c| c.to_ascii_lowercase()) {
+        let (position, _) = candidate.find(|&(_, c)| c == wanted)?;
+        score += match previous {
+            Some(previous) => position - previous - 1,
+            None => position,
+        };
+        previous = Some(position);
+    }
+    Some(score)
+}
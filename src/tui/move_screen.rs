@@ -0,0 +1,163 @@
+use super::popup_area;
+use crate::{
+    move_data::{Field, Flag, MoveData},
+    names::move_id_to_name,
+};
+use ratatui::{
+    Frame,
+    crossterm::event::KeyCode,
+    layout::Constraint,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Clear, HighlightSpacing, Row, Table, TableState},
+};
+
+/// Popup showing the data of one move, where its fields are edited and its flags toggled.
+pub struct MoveScreen {
+    pub id: u16,
+    /// Index in [`Field::ALL`], then in [`Flag::ALL`].
+    selected: usize,
+    /// New value of the selected field, while it is typed.
+    input: Option<String>,
+    error: Option<String>,
+}
+
+pub enum Outcome {
+    Close,
+    Set(Field, i32),
+    Toggle(Flag),
+}
+
+enum Entry {
+    Field(Field),
+    Flag(Flag),
+}
+
+impl MoveScreen {
+    pub const fn new(id: u16) -> Self {
+        Self {
+            id,
+            selected: 0,
+            input: None,
+            error: None,
+        }
+    }
+
+    fn entry(&self) -> Entry {
+        match Field::ALL.get(self.selected) {
+            Some(&field) => Entry::Field(field),
+            None => Entry::Flag(Flag::ALL[self.selected - Field::ALL.len()]),
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode, data: &MoveData) -> Option<Outcome> {
+        self.error = None;
+        let entry = self.entry();
+        if let (Some(input), Entry::Field(field)) = (&mut self.input, entry) {
+            match code {
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => _ = input.pop(),
+                // Typed anywhere, as the input starts with the current value.
+                KeyCode::Char('-') if *field.range().start() < 0 => match input.strip_prefix('-') {
+                    Some(positive) => *input = positive.to_string(),
+                    None => input.insert(0, '-'),
+                },
+                KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 6 => input.push(c),
+                KeyCode::Enter => match field.parse(input) {
+                    Ok(value) => {
+                        self.input = None;
+                        return Some(Outcome::Set(field, value));
+                    }
+                    Err(error) => self.error = Some(error),
+                },
+                _ => {}
+            }
+            return None;
+        }
+        let len = Field::ALL.len() + Flag::ALL.len();
+        match code {
+            KeyCode::Esc | KeyCode::Char('m') => return Some(Outcome::Close),
+            KeyCode::Up => self.selected = (self.selected + len - 1) % len,
+            KeyCode::Down => self.selected = (self.selected + 1) % len,
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = len - 1,
+            KeyCode::Enter | KeyCode::Char(' ') => match self.entry() {
+                Entry::Field(field) => self.input = Some(data.get(field).to_string()),
+                Entry::Flag(flag) => return Some(Outcome::Toggle(flag)),
+            },
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, data: &MoveData) {
+        let rows = Field::ALL.len() + Flag::ALL.len() + 1;
+        let area = popup_area(
+            frame.area(),
+            Constraint::Length(60),
+            Constraint::Length(rows as u16 + 2),
+        );
+        let fields = Field::ALL.iter().enumerate().map(|(index, &field)| {
+            let value = data.get(field);
+            let shown = match &self.input {
+                Some(input) if index == self.selected => Cell::from(format!("{input}_")).yellow(),
+                _ => Cell::from(value.to_string()),
+            };
+            Row::new([
+                Cell::from(field.name()),
+                shown,
+                Cell::from(field.describe(value)),
+            ])
+        });
+        let flags = Flag::ALL.iter().map(|&flag| {
+            let checkbox = if data.has_flag(flag) { "[x]" } else { "[ ]" };
+            Row::new([flag.name(), checkbox])
+        });
+        let widths = [
+            Constraint::Length(30),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+        ];
+        let table = Table::new(
+            fields
+                .chain([Row::new([Cell::from("Flags").bold()])])
+                .chain(flags),
+            widths,
+        )
+        .block(
+            Block::bordered()
+                .title(format!(
+                    "Move data: {} (#{})",
+                    move_id_to_name(self.id.into()),
+                    self.id
+                ))
+                .title_bottom(self.status()),
+        )
+        .row_highlight_style(Style::new().reversed())
+        .highlight_symbol(">>")
+        .highlight_spacing(HighlightSpacing::Always);
+        // The flags come after the row naming them.
+        let row = match self.entry() {
+            Entry::Field(_) => self.selected,
+            Entry::Flag(_) => self.selected + 1,
+        };
+        let mut state = TableState::default().with_selected(row);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn status(&self) -> Line<'static> {
+        match (&self.error, &self.input, self.entry()) {
+            (Some(error), ..) => Line::from(error.clone()).red(),
+            (None, Some(_), Entry::Field(field)) => {
+                let range = field.range();
+                Line::from(format!(
+                    "{} to {}, Enter: confirm, Esc: cancel",
+                    range.start(),
+                    range.end()
+                ))
+            }
+            (None, ..) => Line::from("Enter or Space: edit or toggle, Esc: close"),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::popup_area;
+use crate::check::{Problem, Severity};
+use ratatui::{
+    Frame,
+    crossterm::event::KeyCode,
+    layout::Constraint,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, Paragraph},
+};
+
+/// Popup listing the problems found by [`check`](crate::check::check), to jump to them.
+pub struct Problems {
+    problems: Vec<Problem>,
+    selected: usize,
+}
+
+pub enum Outcome {
+    Cancel,
+    /// Show the learnset of a Pokémon, with the move at the given index selected.
+    Jump(usize, usize),
+}
+
+impl Problems {
+    pub const fn new(problems: Vec<Problem>) -> Self {
+        Self {
+            problems,
+            selected: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Outcome> {
+        match code {
+            KeyCode::Esc | KeyCode::Char('!') => return Some(Outcome::Cancel),
+            KeyCode::Enter => {
+                return self
+                    .problems
+                    .get(self.selected)
+                    .map(|problem| Outcome::Jump(problem.pokemon, problem.index));
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.problems.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup_area(
+            frame.area(),
+            Constraint::Percentage(80),
+            Constraint::Percentage(60),
+        );
+        let block = Block::bordered()
+            .title(format!("Problems ({})", self.problems.len()))
+            .title_bottom("Enter: go to move, Esc: close");
+        frame.render_widget(Clear, area);
+
+        if self.problems.is_empty() {
+            let message = Paragraph::new("No problems found.").block(block);
+            frame.render_widget(message, area);
+            return;
+        }
+        let items = self.problems.iter().map(|problem| {
+            let severity = match problem.severity() {
+                Severity::Error => "error  ".red(),
+                Severity::Warning => "warning".yellow(),
+            };
+            Line::from(vec![severity, format!(" {problem}").into()])
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}